@@ -0,0 +1,112 @@
+// Procedural background below the world grid, see `background.rs`
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct BackgroundLayer {
+    color: vec4<f32>,
+    // x = kind (0 stars, 1 grid), y = parallax, z = spacing, w = size or width
+    params: vec4<f32>,
+};
+
+struct Background {
+    // xy = camera position, z = time, w = dropzone highlight
+    camera: vec4<f32>,
+    // xy = min, zw = max corner of the world
+    bounds: vec4<f32>,
+    color: vec4<f32>,
+    highlight_color: vec4<f32>,
+    // x = strength, y = scale, z = speed, w = world wraps around
+    pattern: vec4<f32>,
+    // x = layers, y = dropzones
+    counts: vec4<u32>,
+    layers: array<BackgroundLayer, 4>,
+    dropzones: array<vec4<f32>, 8>,
+};
+
+@group(2) @binding(0) var<uniform> background: Background;
+
+const HIGHLIGHT_WIDTH: f32 = 48.0;
+
+fn hash(cell: vec2<f32>) -> vec2<f32> {
+    let p = vec2<f32>(dot(cell, vec2<f32>(127.1, 311.7)), dot(cell, vec2<f32>(269.5, 183.3)));
+    return fract(sin(p) * 43758.5453);
+}
+
+fn stars(position: vec2<f32>, spacing: f32, size: f32, time: f32) -> f32 {
+    let cell = floor(position / spacing);
+    let random = hash(cell);
+    let star = (cell + 0.15 + random * 0.7) * spacing;
+    let distance = length(position - star);
+    let twinkle = 0.75 + 0.25 * sin(time * (1.0 + random.x * 2.0) + random.y * 6.283);
+    return (1.0 - smoothstep(size * 0.5, size, distance)) * twinkle;
+}
+
+fn grid(position: vec2<f32>, spacing: f32, width: f32) -> f32 {
+    let offset = abs(fract(position / spacing - 0.5) - 0.5) * spacing;
+    let distance = min(offset.x, offset.y);
+    return 1.0 - smoothstep(width * 0.5, width * 0.5 + fwidth(distance), distance);
+}
+
+// distance to the outline of a rectangle, negative inside
+fn rect_distance(position: vec2<f32>, rect: vec4<f32>) -> f32 {
+    let center = (rect.xy + rect.zw) * 0.5;
+    let half_size = (rect.zw - rect.xy) * 0.5;
+    let d = abs(position - center) - half_size;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+// blend a color with the given alpha over a non-premultiplied color
+fn over(base: vec4<f32>, color: vec3<f32>, alpha: f32) -> vec4<f32> {
+    let a = alpha + base.a * (1.0 - alpha);
+    if a <= 0.0 {
+        return base;
+    }
+    return vec4<f32>((color * alpha + base.rgb * base.a * (1.0 - alpha)) / a, a);
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let position = mesh.world_position.xy;
+    let camera = background.camera.xy;
+    let time = background.camera.z;
+
+    let inside = background.pattern.w > 0.5 || (all(position >= background.bounds.xy) && all(position <= background.bounds.zw));
+    var color = vec4<f32>(0.0);
+    if inside {
+        color = background.color;
+
+        // slow interference of waves, barely visible
+        let scale = background.pattern.y;
+        let t = time * background.pattern.z;
+        let wave = sin(position.x * scale + t) * sin(position.y * scale * 1.3 - t * 0.7)
+            + sin((position.x + position.y) * scale * 0.6 + t * 1.1);
+        color = vec4<f32>(color.rgb * (1.0 + wave * 0.05 * background.pattern.x), color.a);
+    }
+
+    for (var i = 0u; i < background.counts.x; i++) {
+        let layer = background.layers[i];
+        let layer_position = position - camera * (1.0 - layer.params.y);
+        var alpha = 0.0;
+        if layer.params.x < 0.5 {
+            alpha = stars(layer_position, layer.params.z, layer.params.w, time);
+        } else {
+            alpha = grid(layer_position, layer.params.z, layer.params.w);
+        }
+        // layers fade out beyond the edges of the world
+        alpha *= layer.color.a * select(0.4, 1.0, inside);
+        color = over(color, layer.color.rgb, alpha);
+    }
+
+    let highlight = background.camera.w;
+    if highlight > 0.0 {
+        let pulse = 0.5 + 0.5 * sin(time * 4.0);
+        for (var i = 0u; i < background.counts.y; i++) {
+            let distance = rect_distance(position, background.dropzones[i]);
+            if distance > 0.0 && distance < HIGHLIGHT_WIDTH {
+                let alpha = (1.0 - distance / HIGHLIGHT_WIDTH) * highlight * (0.4 + 0.6 * pulse) * background.highlight_color.a;
+                color = over(color, background.highlight_color.rgb, alpha);
+            }
+        }
+    }
+
+    return color;
+}
@@ -0,0 +1,45 @@
+// Import the standard 2d mesh uniforms and set their bind groups
+#import bevy_sprite::mesh2d_functions
+
+// The structure of the vertex buffer is as specified in `specialize()`
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    // x, y = position and z = size
+    @location(3) transform: vec3<f32>,
+    @location(4) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let model = mesh2d_functions::get_model_matrix(0u);
+
+    var position = vertex.position;
+    position.x = position.x * vertex.transform.z + vertex.transform.x;
+    position.y = position.y * vertex.transform.z + vertex.transform.y;
+
+    out.clip_position = mesh2d_functions::mesh2d_position_local_to_clip(model, vec4<f32>(position, 1.0));
+    out.uv = vertex.uv;
+    out.color = vertex.color;
+
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // soft round dot with a bright core
+    let distance = length(in.uv - vec2<f32>(0.5)) * 2.0;
+    let glow = 1.0 - smoothstep(0.0, 1.0, distance);
+    let core = 1.0 - smoothstep(0.2, 0.4, distance);
+    let rgb = mix(in.color.rgb, vec3<f32>(1.0), core * 0.5);
+    return vec4<f32>(rgb, in.color.a * glow);
+}
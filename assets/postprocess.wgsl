@@ -0,0 +1,60 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct PostProcessSettings {
+    // offset of the red and blue channels in pixels
+    aberration: f32,
+    scanlines: f32,
+    crt: f32,
+    time: f32,
+}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+@group(0) @binding(2) var<uniform> settings: PostProcessSettings;
+
+const CRT_CURVATURE: f32 = 0.08;
+const SCANLINE_PERIOD: f32 = 3.0;
+const SCANLINE_DARKNESS: f32 = 0.35;
+
+// bend the uv like the glass of a tube, the corners are pushed outwards
+fn curve(uv: vec2<f32>) -> vec2<f32> {
+    let centered = uv * 2.0 - 1.0;
+    let bent = centered * (1.0 + CRT_CURVATURE * dot(centered.yx, centered.yx));
+    return bent * 0.5 + 0.5;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let uv = mix(in.uv, curve(in.uv), settings.crt);
+    // black outside of the curved screen, sampling stays in uniform control flow
+    let inside = all(uv >= vec2(0.0)) && all(uv <= vec2(1.0));
+
+    // red and blue are pushed apart from the center of the screen
+    let direction = uv - 0.5;
+    let offset = direction / max(length(direction), 0.0001) * settings.aberration / size;
+    let center = textureSample(screen_texture, screen_sampler, uv);
+    var color = vec3(
+        textureSample(screen_texture, screen_sampler, uv + offset).r,
+        center.g,
+        textureSample(screen_texture, screen_sampler, uv - offset).b,
+    );
+
+    let row = uv.y * size.y / SCANLINE_PERIOD;
+    let scanline = 1.0 - SCANLINE_DARKNESS * (0.5 + 0.5 * cos(row * 6.2831853));
+    color *= mix(1.0, scanline, settings.scanlines);
+
+    // shadow mask of the phosphors, a vignette and a little flicker
+    let column = u32(uv.x * size.x) % 3u;
+    let mask = vec3(
+        select(0.8, 1.1, column == 0u),
+        select(0.8, 1.1, column == 1u),
+        select(0.8, 1.1, column == 2u),
+    );
+    let vignette = 16.0 * uv.x * uv.y * (1.0 - uv.x) * (1.0 - uv.y);
+    let flicker = 1.0 - 0.015 * sin(settings.time * 110.0);
+    let crt = mask * pow(vignette, 0.25) * flicker;
+    color *= mix(vec3(1.0), crt, settings.crt);
+
+    return select(vec4(0.0, 0.0, 0.0, 1.0), vec4(color, center.a), inside);
+}
@@ -13,13 +13,21 @@ struct Vertex {
     // x, y = offset and z = rotation in radians
     @location(3) transform: vec3<f32>,
     @location(4) sheet_index: u32,
-    @location(5) uv_offset: vec2<f32>,
+    // xy = min corner and zw = size of the tile in uv coordinates
+    @location(5) uv_rect: vec4<f32>,
+    // rgb is multiplied with the texture, a is the alpha
+    @location(6) tint: vec4<f32>,
+    // 0 = unchanged, 1 = white
+    @location(7) highlight: f32,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) layer: u32,
+    @location(3) tint: vec4<f32>,
+    @location(4) highlight: f32,
+    @location(5) uv_rect: vec4<f32>,
 };
 
 /// Entry point for the vertex shader
@@ -42,14 +50,24 @@ fn vertex(vertex: Vertex) -> VertexOutput {
     position.y = rotated_y + transform_y;
 
     out.clip_position = mesh2d_functions::mesh2d_position_local_to_clip(model, vec4<f32>(position, 1.0));
-    out.uv = ((vertex.uv * 64.0) + vertex.uv_offset) / vec2<f32>(2048.0, 2048.0);
+    out.uv = vertex.uv_rect.xy + vertex.uv * vertex.uv_rect.zw;
     out.layer = vertex.sheet_index;
+    out.tint = vertex.tint;
+    out.highlight = vertex.highlight;
+    out.uv_rect = vertex.uv_rect;
 
     return out;
 }
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-   let color = textureSample(icon_sheets, icon_sheets_sampler, in.uv, in.layer);
-   return color;
+   // keep the bilinear footprint of the coarser sampled mip level inside of the tile
+   let size = vec2<f32>(textureDimensions(icon_sheets));
+   let texels = in.uv * size;
+   let max_lod = f32(textureNumLevels(icon_sheets) - 1u);
+   let lod = clamp(log2(max(length(dpdx(texels)), length(dpdy(texels)))), 0.0, max_lod);
+   let half_texel = min(0.5 * exp2(ceil(lod)) / size, in.uv_rect.zw * 0.5);
+   let uv = clamp(in.uv, in.uv_rect.xy + half_texel, in.uv_rect.xy + in.uv_rect.zw - half_texel);
+   let color = textureSample(icon_sheets, icon_sheets_sampler, uv, in.layer) * in.tint;
+   return vec4<f32>(mix(color.rgb, vec3<f32>(1.0), in.highlight), color.a);
 }
\ No newline at end of file
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use super::settings::SettingsResource;
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_ui_scale_system, update_game_speed_system)
+                .run_if(resource_changed::<SettingsResource>),
+        );
+    }
+}
+
+fn update_ui_scale_system(settings: Res<SettingsResource>, mut ui_scale: ResMut<UiScale>) {
+    if ui_scale.0 != settings.ui_scale {
+        ui_scale.0 = settings.ui_scale;
+    }
+}
+
+/// Everything that runs on the virtual clock slows down, the music keeps its pace
+fn update_game_speed_system(settings: Res<SettingsResource>, mut time: ResMut<Time<Virtual>>) {
+    if time.relative_speed() != settings.game_speed {
+        time.set_relative_speed(settings.game_speed);
+    }
+}
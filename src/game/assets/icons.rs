@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use bevy::{
     asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
@@ -5,6 +7,8 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::game::world::WorldLayout;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Icon {
     pub name: String,
@@ -21,6 +25,9 @@ pub struct IconSheetFile {
     pub height: usize,
     pub tile_width: usize,
     pub tile_height: usize,
+    /// Transparent pixels between tiles, half of it on each side
+    #[serde(default)]
+    pub gap: usize,
     pub tiles: Vec<Icon>,
 }
 
@@ -32,15 +39,87 @@ pub struct IconSheet {
     pub height: usize,
     pub tile_width: usize,
     pub tile_height: usize,
+    pub gap: usize,
     pub tiles: Vec<Icon>,
 }
 
 #[derive(Asset, Debug, TypePath)]
 pub struct IconSheetAsset(pub Vec<IconSheet>);
 
+impl IconSheetAsset {
+    pub fn contains(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|sheet| sheet.tiles.iter().any(|icon| icon.name == name))
+    }
+}
+
 #[derive(Default)]
 pub struct IconSheetLoader;
 
+/// IconPackFile as represented in the pack manifest
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IconPackFile {
+    pub name: String,
+    /// Sheet JSON or SVG bundle of the pack, relative to the manifest
+    pub sheets: String,
+    /// Replaces the player icon of the levels
+    #[serde(default)]
+    pub player_icon: Option<String>,
+    #[serde(default)]
+    pub credits: String,
+}
+
+/// IconPack with the sheets handle dependency
+#[derive(Debug, Clone)]
+pub struct IconPack {
+    pub name: String,
+    pub handle: Handle<IconSheetAsset>,
+    pub player_icon: Option<String>,
+    pub credits: String,
+}
+
+impl IconPack {
+    /// Checks what can only be checked once the sheets are loaded, without a player icon of
+    /// its own the pack needs the player icon of every level
+    pub fn validate(
+        &self,
+        sheets: &IconSheetAsset,
+        levels: &[WorldLayout],
+    ) -> Result<(), AssetError> {
+        if sheets.0.is_empty() {
+            return Err(AssetError::EmptyPack {
+                pack: self.name.clone(),
+            });
+        }
+        match &self.player_icon {
+            Some(name) if !sheets.contains(name) => Err(AssetError::MissingPlayerIcon {
+                pack: self.name.clone(),
+                name: name.clone(),
+            }),
+            Some(_) => Ok(()),
+            None => match levels
+                .iter()
+                .find(|level| !sheets.contains(&level.player_icon))
+            {
+                Some(level) => Err(AssetError::MissingLevelPlayerIcon {
+                    pack: self.name.clone(),
+                    name: level.player_icon.clone(),
+                    level: level.name.clone(),
+                }),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+#[derive(Asset, Debug, TypePath)]
+pub struct IconPackListAsset(pub Vec<IconPack>);
+
+#[derive(Default)]
+pub struct IconPackListLoader;
+
 #[derive(thiserror::Error, Debug)]
 pub enum AssetError {
     #[error("Asset Error: {0}")]
@@ -51,6 +130,94 @@ pub enum AssetError {
 
     #[error("Asset JSON Error: {0}")]
     YamlError(#[from] serde_json::Error),
+
+    #[error("Asset Read Error: {0}")]
+    ReadAssetBytes(#[from] bevy::asset::ReadAssetBytesError),
+
+    #[error("Asset Load Error: {0}")]
+    LoadDirect(Box<bevy::asset::LoadDirectError>),
+
+    #[error("Icon sheet {sheet} has no tiles")]
+    NoTiles { sheet: String },
+
+    #[error("Icon {name} at {x}, {y} is outside of the {width}x{height} icon sheet {sheet}")]
+    TileOutOfBounds {
+        sheet: String,
+        name: String,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+
+    #[error(
+        "Icon sheet {sheet} is {width}x{height} but its image is {image_width}x{image_height}"
+    )]
+    SheetSizeMismatch {
+        sheet: String,
+        width: usize,
+        height: usize,
+        image_width: usize,
+        image_height: usize,
+    },
+
+    #[error("Icon pack {pack} has no player icon {name}")]
+    MissingPlayerIcon { pack: String, name: String },
+
+    #[error("Icon pack {pack} has no playerIcon and no player icon {name} of level {level}")]
+    MissingLevelPlayerIcon {
+        pack: String,
+        name: String,
+        level: String,
+    },
+
+    #[error("Icon pack manifest lists no packs")]
+    NoPacks,
+
+    #[error("Icon pack manifest failed to load")]
+    PacksNotLoaded,
+
+    #[error("No icon pack loaded")]
+    NoPackLoaded,
+
+    #[error("Icon pack {pack} is not loaded")]
+    PackNotLoaded { pack: String },
+
+    #[error("Icon pack {pack} has no icon sheets")]
+    EmptyPack { pack: String },
+
+    #[error("Icon sheet {sheet} of pack {pack} has no image or it is not convertible to rgba")]
+    BrokenSheet { pack: String, sheet: String },
+}
+
+impl From<bevy::asset::LoadDirectError> for AssetError {
+    fn from(err: bevy::asset::LoadDirectError) -> Self {
+        AssetError::LoadDirect(Box::new(err))
+    }
+}
+
+impl IconSheetFile {
+    /// Every tile has to be inside of the sheet
+    pub fn validate(&self) -> Result<(), AssetError> {
+        if self.tiles.is_empty() {
+            return Err(AssetError::NoTiles {
+                sheet: self.filename.clone(),
+            });
+        }
+        match self.tiles.iter().find(|icon| {
+            icon.x + self.tile_width > self.width || icon.y + self.tile_height > self.height
+        }) {
+            Some(icon) => Err(AssetError::TileOutOfBounds {
+                sheet: self.filename.clone(),
+                name: icon.name.clone(),
+                x: icon.x,
+                y: icon.y,
+                width: self.width,
+                height: self.height,
+            }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl AssetLoader for IconSheetLoader {
@@ -67,22 +234,45 @@ impl AssetLoader for IconSheetLoader {
         Box::pin(async move {
             let mut bytes = Vec::new();
             reader.read_to_end(&mut bytes).await?;
-            let sheets = serde_json::from_slice::<Vec<IconSheetFile>>(&bytes)?
-                .into_iter()
-                .map(|sheet| {
-                    // load sheet as a dependency
-                    info!("Load icon sheet: {:?}", sheet.filename);
-                    let handle: Handle<Image> = load_context.load(&sheet.filename);
-                    IconSheet {
-                        handle,
+            let directory = load_context
+                .path()
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+
+            let mut sheets = Vec::new();
+            for sheet in serde_json::from_slice::<Vec<IconSheetFile>>(&bytes)? {
+                sheet.validate()?;
+
+                // load the sheet right away to check its size
+                info!("Load icon sheet: {:?}", sheet.filename);
+                let image = load_context
+                    .load_direct(directory.join(&sheet.filename))
+                    .await?
+                    .take::<Image>()
+                    .ok_or_else(|| anyhow::anyhow!("{} is not an image", sheet.filename))?;
+                let (image_width, image_height) = (image.width() as usize, image.height() as usize);
+                if image_width != sheet.width || image_height != sheet.height {
+                    return Err(AssetError::SheetSizeMismatch {
+                        sheet: sheet.filename,
                         width: sheet.width,
                         height: sheet.height,
-                        tile_width: sheet.tile_width,
-                        tile_height: sheet.tile_height,
-                        tiles: sheet.tiles,
-                    }
-                })
-                .collect();
+                        image_width,
+                        image_height,
+                    });
+                }
+                let handle = load_context.add_labeled_asset(sheet.filename.clone(), image);
+
+                sheets.push(IconSheet {
+                    handle,
+                    width: sheet.width,
+                    height: sheet.height,
+                    tile_width: sheet.tile_width,
+                    tile_height: sheet.tile_height,
+                    gap: sheet.gap,
+                    tiles: sheet.tiles,
+                });
+            }
             Ok(IconSheetAsset(sheets))
         })
     }
@@ -91,3 +281,48 @@ impl AssetLoader for IconSheetLoader {
         &["icon.json"]
     }
 }
+
+impl AssetLoader for IconPackListLoader {
+    type Asset = IconPackListAsset;
+    type Settings = ();
+    type Error = AssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let directory = load_context
+                .path()
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+
+            let packs = serde_json::from_slice::<Vec<IconPackFile>>(&bytes)?
+                .into_iter()
+                .map(|pack| {
+                    // load the sheets of every pack as a dependency
+                    info!("Load icon pack: {:?}", pack.name);
+                    IconPack {
+                        name: pack.name,
+                        handle: load_context.load(directory.join(&pack.sheets)),
+                        player_icon: pack.player_icon,
+                        credits: pack.credits,
+                    }
+                })
+                .collect::<Vec<_>>();
+            if packs.is_empty() {
+                return Err(AssetError::NoPacks);
+            }
+            Ok(IconPackListAsset(packs))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["packs.json"]
+    }
+}
@@ -0,0 +1,298 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use super::icons::AssetError;
+use crate::game::background::BackgroundStyle;
+use crate::game::world::{
+    DropzoneLayout, IconFilter, Obstacle, SpawnRegion, WinCondition, WorldLayout,
+};
+
+/// DropzoneFile as represented in the JSON file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DropzoneFile {
+    pub center: [f32; 2],
+    pub size: [f32; 2],
+    #[serde(default)]
+    pub velocity: [f32; 2],
+    #[serde(default)]
+    pub shrink_rate: f32,
+    /// Defaults to the initial size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_size: Option<[f32; 2]>,
+}
+
+/// ObstacleFile as represented in the JSON file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ObstacleFile {
+    Rectangle { center: [f32; 2], size: [f32; 2] },
+    Circle { center: [f32; 2], radius: f32 },
+    Polygon { points: Vec<[f32; 2]> },
+}
+
+/// SpawnRegionFile as represented in the JSON file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnRegionFile {
+    pub center: [f32; 2],
+    pub size: [f32; 2],
+}
+
+/// LevelFile as represented in the JSON file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelFile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub size: [f32; 2],
+    pub grid_spacing: f32,
+    pub player_start: [f32; 2],
+    pub player_icon: String,
+    #[serde(default)]
+    pub icons: IconFilter,
+    pub dropzones: Vec<DropzoneFile>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleFile>,
+    #[serde(default)]
+    pub spawn_regions: Vec<SpawnRegionFile>,
+    /// Leaving the world through an edge re-enters it on the opposite side
+    #[serde(default)]
+    pub wrap: bool,
+    #[serde(default)]
+    pub background: BackgroundStyle,
+    /// Fields of the `SettingsResource` to override, by name in camelCase like the other keys
+    #[serde(default)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub win_condition: WinCondition,
+}
+
+impl TryFrom<LevelFile> for WorldLayout {
+    type Error = AssetError;
+
+    fn try_from(level: LevelFile) -> Result<Self, Self::Error> {
+        let size = Vec2::from(level.size);
+        if size.cmple(Vec2::ZERO).any() || level.grid_spacing <= 0.0 {
+            return Err(anyhow::anyhow!("Level {:?} has an empty world", level.name).into());
+        }
+        if level.dropzones.is_empty() {
+            return Err(anyhow::anyhow!("Level {:?} has no dropzones", level.name).into());
+        }
+
+        let obstacles = level
+            .obstacles
+            .into_iter()
+            .map(|obstacle| match obstacle {
+                ObstacleFile::Rectangle { center, size } => Ok(Obstacle::Rectangle {
+                    center: center.into(),
+                    size: size.into(),
+                }),
+                ObstacleFile::Circle { center, radius } => Ok(Obstacle::Circle {
+                    center: center.into(),
+                    radius,
+                }),
+                ObstacleFile::Polygon { points } if points.len() < 3 => Err(anyhow::anyhow!(
+                    "Level {:?} has a polygon with less than 3 points",
+                    level.name
+                )),
+                ObstacleFile::Polygon { points } => Ok(Obstacle::Polygon {
+                    points: points.into_iter().map(Vec2::from).collect(),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if level
+            .spawn_regions
+            .iter()
+            .any(|region| region.size.iter().any(|size| *size <= 0.0))
+        {
+            return Err(anyhow::anyhow!("Level {:?} has an empty spawn region", level.name).into());
+        }
+        let spawn_regions = level
+            .spawn_regions
+            .into_iter()
+            .map(|region| SpawnRegion {
+                center: region.center.into(),
+                size: region.size.into(),
+            })
+            .collect::<Vec<_>>();
+        // icons never find a free position in a region that is covered completely
+        let covered = spawn_regions.iter().any(|region| {
+            let corners = region.corners();
+            let in_dropzone = level.dropzones.iter().any(|dropzone| {
+                let center = Vec2::from(dropzone.center);
+                let half = Vec2::from(dropzone.size) / 2.0;
+                corners
+                    .iter()
+                    .all(|corner| (*corner - center).abs().cmple(half).all())
+            });
+            let in_obstacle = obstacles.iter().any(|obstacle| {
+                obstacle.contains(region.center)
+                    && corners.iter().all(|corner| obstacle.contains(*corner))
+            });
+            in_dropzone || in_obstacle
+        });
+        if covered {
+            return Err(anyhow::anyhow!(
+                "Level {:?} has a spawn region inside of a dropzone or obstacle",
+                level.name
+            )
+            .into());
+        }
+        if let Err(err) = level.background.validate() {
+            return Err(anyhow::anyhow!("Level {:?} has {}", level.name, err).into());
+        }
+
+        Ok(WorldLayout {
+            name: level.name,
+            description: level.description,
+            size,
+            grid_spacing: level.grid_spacing,
+            player_start: level.player_start.into(),
+            player_icon: level.player_icon,
+            icons: level.icons,
+            dropzones: level
+                .dropzones
+                .into_iter()
+                .map(|dropzone| DropzoneLayout {
+                    center: dropzone.center.into(),
+                    size: dropzone.size.into(),
+                    velocity: dropzone.velocity.into(),
+                    shrink_rate: dropzone.shrink_rate,
+                    min_size: dropzone.min_size.unwrap_or(dropzone.size).into(),
+                })
+                .collect(),
+            obstacles,
+            spawn_regions,
+            wrap: level.wrap,
+            background: level.background,
+            settings: level
+                .settings
+                .into_iter()
+                .map(|(name, value)| (snake_case(&name), value))
+                .collect(),
+            win_condition: level.win_condition,
+        })
+    }
+}
+
+impl From<&WorldLayout> for LevelFile {
+    fn from(layout: &WorldLayout) -> Self {
+        LevelFile {
+            name: layout.name.clone(),
+            description: layout.description.clone(),
+            size: layout.size.into(),
+            grid_spacing: layout.grid_spacing,
+            player_start: layout.player_start.into(),
+            player_icon: layout.player_icon.clone(),
+            icons: layout.icons.clone(),
+            dropzones: layout
+                .dropzones
+                .iter()
+                .map(|dropzone| DropzoneFile {
+                    center: dropzone.center.into(),
+                    size: dropzone.size.into(),
+                    velocity: dropzone.velocity.into(),
+                    shrink_rate: dropzone.shrink_rate,
+                    min_size: (dropzone.min_size != dropzone.size)
+                        .then_some(dropzone.min_size.into()),
+                })
+                .collect(),
+            obstacles: layout
+                .obstacles
+                .iter()
+                .map(|obstacle| match obstacle {
+                    Obstacle::Rectangle { center, size } => ObstacleFile::Rectangle {
+                        center: (*center).into(),
+                        size: (*size).into(),
+                    },
+                    Obstacle::Circle { center, radius } => ObstacleFile::Circle {
+                        center: (*center).into(),
+                        radius: *radius,
+                    },
+                    Obstacle::Polygon { points } => ObstacleFile::Polygon {
+                        points: points.iter().map(|point| (*point).into()).collect(),
+                    },
+                })
+                .collect(),
+            spawn_regions: layout
+                .spawn_regions
+                .iter()
+                .map(|region| SpawnRegionFile {
+                    center: region.center.into(),
+                    size: region.size.into(),
+                })
+                .collect(),
+            wrap: layout.wrap,
+            background: layout.background.clone(),
+            settings: layout
+                .settings
+                .iter()
+                .map(|(name, value)| (camel_case(name), value.clone()))
+                .collect(),
+            win_condition: layout.win_condition.clone(),
+        }
+    }
+}
+
+/// Setting name of the level file to the field name, e.g. `projectileCooldown`
+fn snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            result.push('_');
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+/// Field name to the setting name of the level file, e.g. `projectile_cooldown`
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            result.push(first.to_ascii_uppercase());
+            result.extend(chars);
+        }
+    }
+    result
+}
+
+#[derive(Asset, Debug, TypePath)]
+pub struct LevelAsset(pub WorldLayout);
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = AssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let level = serde_json::from_slice::<LevelFile>(&bytes)?;
+            Ok(LevelAsset(level.try_into()?))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.json"]
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::{Deserialize, Serialize};
+
+use super::icons::AssetError;
+use crate::game::locale::{Locale, LocaleFonts, TextKey};
+
+/// Font paths of a locale as represented in the JSON file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocaleFontsFile {
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub text2: Option<String>,
+}
+
+/// LocaleFile as represented in the JSON file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocaleFile {
+    /// Name of the language in the language itself
+    pub name: String,
+    /// Fonts covering the script of the language, missing ones keep the default fonts
+    #[serde(default)]
+    pub fonts: LocaleFontsFile,
+    /// Texts by key, missing keys keep the English text
+    pub texts: HashMap<TextKey, String>,
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct LocaleAsset(pub Locale);
+
+#[derive(Default)]
+pub struct LocaleLoader;
+
+impl AssetLoader for LocaleLoader {
+    type Asset = LocaleAsset;
+    type Settings = ();
+    type Error = AssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file = serde_json::from_slice::<LocaleFile>(&bytes)?;
+            // the fonts are dependencies, the locale is only ready once they loaded
+            let mut font = |path: Option<String>| path.map(|path| load_context.load(path));
+            let fonts = LocaleFonts {
+                title: font(file.fonts.title),
+                text: font(file.fonts.text),
+                text2: font(file.fonts.text2),
+            };
+            Ok(LocaleAsset(Locale::new(file.name, file.texts, fonts)))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["locale.json"]
+    }
+}
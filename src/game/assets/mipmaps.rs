@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+/// Texels outside of every tile
+const NO_TILE: u32 = u32::MAX;
+
+/// Number of mip levels of a layer, tiles `gap` pixels apart stop sharing
+/// texels with their neighbours after the last one
+pub fn mip_level_count(width: u32, height: u32, gap: usize) -> u32 {
+    let full = u32::BITS - width.max(height).leading_zeros();
+    let gutter = (usize::BITS - gap.leading_zeros()).max(1);
+    full.min(gutter)
+}
+
+/// Tile of every pixel of the first level, the texels of the other levels
+/// belong to the tile under their center
+struct TileMap {
+    tiles: Vec<u32>,
+    width: u32,
+    height: u32,
+}
+
+impl TileMap {
+    fn new(width: u32, height: u32, rects: &[URect]) -> Self {
+        let mut tiles = vec![NO_TILE; (width * height) as usize];
+        for (index, rect) in rects.iter().enumerate() {
+            for y in rect.min.y..rect.max.y.min(height) {
+                let row = (y * width) as usize;
+                let (start, end) = (rect.min.x.min(width), rect.max.x.min(width));
+                tiles[row + start as usize..row + end as usize].fill(index as u32);
+            }
+        }
+        Self {
+            tiles,
+            width,
+            height,
+        }
+    }
+
+    fn get(&self, level: u32, x: u32, y: u32) -> u32 {
+        let half = (1 << level) >> 1;
+        let x = ((x << level) + half).min(self.width - 1);
+        let y = ((y << level) + half).min(self.height - 1);
+        self.tiles[(y * self.width + x) as usize]
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Half the size of a rgba8 srgb level, colors are averaged in linear space
+/// weighted by alpha, so transparent pixels don't darken the edges. Only the
+/// texels of the same tile are averaged, so tiles never bleed into each other
+fn downsample(
+    src: &[u8],
+    level: u32,
+    width: u32,
+    height: u32,
+    tiles: &TileMap,
+    lut: &[f32; 256],
+) -> Vec<u8> {
+    let (dst_width, dst_height) = ((width / 2).max(1), (height / 2).max(1));
+    let mut dst = Vec::with_capacity((dst_width * dst_height * 4) as usize);
+    for y in 0..dst_height {
+        for x in 0..dst_width {
+            let tile = tiles.get(level + 1, x, y);
+            let texels = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(dx, dy)| ((x * 2 + dx).min(width - 1), (y * 2 + dy).min(height - 1)));
+            // clamp to the tile, tiles smaller than a texel keep all of them
+            let inside = texels
+                .iter()
+                .filter(|(sx, sy)| tiles.get(level, *sx, *sy) == tile)
+                .count();
+            let mut color = Vec3::ZERO;
+            let mut alpha = 0.0;
+            for (sx, sy) in texels {
+                if inside > 0 && tiles.get(level, sx, sy) != tile {
+                    continue;
+                }
+                let i = ((sy * width + sx) * 4) as usize;
+                let a = src[i + 3] as f32 / 255.0;
+                color += Vec3::new(
+                    lut[src[i] as usize],
+                    lut[src[i + 1] as usize],
+                    lut[src[i + 2] as usize],
+                ) * a;
+                alpha += a;
+            }
+            if alpha > 0.0 {
+                color /= alpha;
+            }
+            let count = if inside > 0 { inside } else { texels.len() };
+            dst.extend_from_slice(&[
+                linear_to_srgb(color.x),
+                linear_to_srgb(color.y),
+                linear_to_srgb(color.z),
+                (alpha / count as f32 * 255.0).round() as u8,
+            ]);
+        }
+    }
+    dst
+}
+
+/// Appends the mip levels after the first one to `data`, which holds the
+/// first level of a single layer with the tiles at `rects`
+pub fn append_mip_chain(data: &mut Vec<u8>, width: u32, height: u32, levels: u32, rects: &[URect]) {
+    let lut: [f32; 256] = std::array::from_fn(|value| srgb_to_linear(value as u8));
+    let tiles = TileMap::new(width, height, rects);
+    let (mut start, mut width, mut height) = (0, width, height);
+    for level in 0..levels.saturating_sub(1) {
+        let next = downsample(&data[start..], level, width, height, &tiles, &lut);
+        start = data.len();
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+        data.extend_from_slice(&next);
+    }
+}
@@ -1,19 +1,54 @@
 use std::collections::HashSet;
 
-use bevy::asset::RecursiveDependencyLoadState;
+use bevy::app::AppExit;
+use bevy::asset::{LoadState, RecursiveDependencyLoadState};
 use bevy::prelude::*;
 use bevy::render::render_asset::RenderAssetUsages;
-use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
-use bevy::render::texture::{ImageFilterMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::render::render_resource::{
+    Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+};
 
-use self::icons::IconSheetAsset;
+use self::icons::{AssetError, IconPack, IconPackListAsset, IconSheetAsset};
+use self::levels::LevelAsset;
+use self::locales::LocaleAsset;
+use self::themes::ThemeListAsset;
 
 use super::audio::AudioFileResource;
 use super::hud::FontResource;
 use super::icons::IconSheetResource;
+use super::locale::LocaleResource;
 use super::states::GameState;
+use super::theme::ThemeResource;
+use super::world::{WorldLayout, WorldLayoutResource};
 
 pub mod icons;
+pub mod levels;
+pub mod locales;
+mod mipmaps;
+pub mod svg_icons;
+pub mod themes;
+
+/// Icon packs of the pack select screen, the first one is used by default
+const ICON_PACKS: &str = "icons.packs.json";
+
+/// Themes of the theme switcher, the first one is used by default
+const THEMES: &str = "themes.json";
+
+/// Languages of the language switcher, after the built-in English
+const LOCALES: [&str; 3] = [
+    "locales/de.locale.json",
+    "locales/ru.locale.json",
+    "locales/ko.locale.json",
+];
+
+/// Levels bundled with the game, in the order of the level select screen
+const LEVELS: [&str; 5] = [
+    "levels/classic.level.json",
+    "levels/wide.level.json",
+    "levels/drift.level.json",
+    "levels/arena.level.json",
+    "levels/torus.level.json",
+];
 
 pub struct GameAssetPlugin;
 
@@ -21,12 +56,22 @@ impl Plugin for GameAssetPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<icons::IconSheetAsset>();
         app.init_asset_loader::<icons::IconSheetLoader>();
+        app.init_asset_loader::<svg_icons::SvgIconSheetLoader>();
+        app.init_asset::<icons::IconPackListAsset>();
+        app.init_asset_loader::<icons::IconPackListLoader>();
+        app.init_asset::<levels::LevelAsset>();
+        app.init_asset_loader::<levels::LevelLoader>();
+        app.init_asset::<themes::ThemeListAsset>();
+        app.init_asset_loader::<themes::ThemeListLoader>();
+        app.init_asset::<locales::LocaleAsset>();
+        app.init_asset_loader::<locales::LocaleLoader>();
         app.add_systems(OnEnter(GameState::Init), load_assets_system);
         app.add_systems(
             Update,
             update_loading_system.run_if(in_state(GameState::AssetsLoading)),
         );
         app.add_systems(OnEnter(GameState::AssetsLoaded), assets_loaded_system);
+        app.add_systems(OnEnter(GameState::GameLoading), load_icon_pack_system);
     }
 }
 
@@ -36,15 +81,50 @@ pub struct PendingAssets {
     pub n_total: usize,
 }
 
+#[derive(Resource, Debug)]
+pub struct LevelsResource {
+    pub handles: Vec<Handle<LevelAsset>>,
+}
+
+#[derive(Resource, Debug)]
+pub struct IconPacksResource {
+    pub handle: Handle<IconPackListAsset>,
+    /// Packs that loaded and passed validation, in the order of the manifest
+    pub packs: Vec<IconPack>,
+    pub current: usize,
+}
+
+impl IconPacksResource {
+    pub fn current(&self) -> Option<&IconPack> {
+        self.packs.get(self.current)
+    }
+}
+
 fn load_assets_system(
     mut commands: Commands,
     server: Res<AssetServer>,
     mut state: ResMut<NextState<GameState>>,
+    mut themes: ResMut<ThemeResource>,
+    mut locales: ResMut<LocaleResource>,
 ) {
     let mut pending = HashSet::new();
 
-    let icons: Handle<IconSheetAsset> = server.load("icons.icon.json");
-    pending.insert(icons.clone().untyped());
+    themes.handle = server.load(THEMES);
+    pending.insert(themes.handle.clone().untyped());
+
+    let packs: Handle<IconPackListAsset> = server.load(ICON_PACKS);
+    pending.insert(packs.clone().untyped());
+
+    let levels: Vec<Handle<LevelAsset>> = LEVELS.iter().map(|path| server.load(*path)).collect();
+    pending.extend(levels.iter().map(|handle| handle.clone().untyped()));
+
+    locales.handles = LOCALES.iter().map(|path| server.load(*path)).collect();
+    pending.extend(
+        locales
+            .handles
+            .iter()
+            .map(|handle| handle.clone().untyped()),
+    );
 
     let font_title: Handle<Font> = server.load("fonts/GasoekOne-Regular.ttf");
     let font_text: Handle<Font> = server.load("fonts/DMSans-Black.ttf");
@@ -69,15 +149,24 @@ fn load_assets_system(
     pending.insert(capture.clone().untyped());
     pending.insert(damage.clone().untyped());
 
+    commands.insert_resource(IconPacksResource {
+        handle: packs,
+        packs: Vec::new(),
+        current: 0,
+    });
     commands.insert_resource(IconSheetResource {
-        handle: icons.clone(),
+        handle: Handle::default(),
         texture_array: None,
+        texture_array_size: Vec2::ZERO,
     });
-    commands.insert_resource(FontResource {
+    commands.insert_resource(LevelsResource { handles: levels });
+    let fonts = FontResource {
         title: font_title.clone(),
         text: font_text.clone(),
         text2: font_text2.clone(),
-    });
+    };
+    locales.default_fonts = fonts.clone();
+    commands.insert_resource(fonts);
     commands.insert_resource(AudioFileResource {
         music,
         shoot,
@@ -126,50 +215,234 @@ fn update_loading_system(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn assets_loaded_system(
     mut state: ResMut<NextState<GameState>>,
+    mut packs: ResMut<IconPacksResource>,
+    pack_assets: Res<Assets<IconPackListAsset>>,
+    assets: Res<Assets<IconSheetAsset>>,
+    levels: Res<LevelsResource>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut layouts: ResMut<WorldLayoutResource>,
+    mut themes: ResMut<ThemeResource>,
+    theme_assets: Res<Assets<ThemeListAsset>>,
+    mut locales: ResMut<LocaleResource>,
+    locale_assets: Res<Assets<LocaleAsset>>,
+    server: Res<AssetServer>,
+    mut exit: EventWriter<AppExit>,
+) {
+    // keep the built-in theme if the themes failed to load
+    if let Some(ThemeListAsset(list)) = theme_assets.get(&themes.handle) {
+        themes.set_themes(list.clone());
+    }
+
+    // locales that failed to load are skipped, their fonts that failed fall back to the defaults
+    let failed = |font: &mut Option<Handle<Font>>| {
+        if font
+            .as_ref()
+            .is_some_and(|font| server.load_state(font) == LoadState::Failed)
+        {
+            *font = None;
+        }
+    };
+    let loaded = locales
+        .handles
+        .iter()
+        .filter_map(|handle| locale_assets.get(handle))
+        .map(|LocaleAsset(locale)| {
+            let mut locale = locale.clone();
+            failed(&mut locale.fonts.title);
+            failed(&mut locale.fonts.text);
+            failed(&mut locale.fonts.text2);
+            locale
+        })
+        .collect::<Vec<_>>();
+    locales.add_locales(loaded);
+
+    // levels that failed to load are skipped, keep the built-in level if none loaded
+    let loaded = levels
+        .handles
+        .iter()
+        .filter_map(|handle| level_assets.get(handle))
+        .map(|LevelAsset(layout)| layout.clone())
+        .collect::<Vec<_>>();
+    if !loaded.is_empty() {
+        layouts.layouts = loaded;
+        layouts.current = 0;
+    }
+
+    match loaded_icon_packs(&packs.handle, &pack_assets, &assets, &layouts.layouts) {
+        Ok(loaded) => {
+            packs.packs = loaded;
+            packs.current = 0;
+            state.set(GameState::GameLoading);
+        }
+        Err(err) => {
+            // the game can't spawn any icons without a pack
+            error!("{}", err);
+            exit.send(AppExit);
+        }
+    }
+}
+
+/// Packs that failed to load or validate are skipped like the levels
+fn loaded_icon_packs(
+    handle: &Handle<IconPackListAsset>,
+    pack_assets: &Assets<IconPackListAsset>,
+    assets: &Assets<IconSheetAsset>,
+    levels: &[WorldLayout],
+) -> Result<Vec<IconPack>, AssetError> {
+    let IconPackListAsset(list) = pack_assets.get(handle).ok_or(AssetError::PacksNotLoaded)?;
+    let loaded = list
+        .iter()
+        .filter(|pack| {
+            let Some(sheets) = assets.get(&pack.handle) else {
+                error!(
+                    "Skipping icon pack: {}",
+                    AssetError::PackNotLoaded {
+                        pack: pack.name.clone(),
+                    }
+                );
+                return false;
+            };
+            pack.validate(sheets, levels)
+                .map_err(|err| error!("Skipping icon pack: {}", err))
+                .is_ok()
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if loaded.is_empty() {
+        return Err(AssetError::NoPackLoaded);
+    }
+    Ok(loaded)
+}
+
+/// Build the texture array of the selected pack, unless it is already built
+fn load_icon_pack_system(
+    packs: Res<IconPacksResource>,
     mut resource: ResMut<IconSheetResource>,
     assets: Res<Assets<IconSheetAsset>>,
     mut images: ResMut<Assets<Image>>,
+    mut exit: EventWriter<AppExit>,
 ) {
-    let IconSheetAsset(sheets) = assets.get(&resource.handle).unwrap();
-    let (width, height) = sheets
-        .first()
-        .map(|sheet| (sheet.width, sheet.height))
-        .unwrap();
+    let Some(pack) = packs.current() else {
+        error!("{}", AssetError::NoPackLoaded);
+        exit.send(AppExit);
+        return;
+    };
+    if resource.texture_array.is_some() && resource.handle == pack.handle {
+        return;
+    }
+    info!("Using icon pack: {}", pack.name);
+
+    match icon_texture_array(pack, &assets, &images) {
+        Ok((texture_array, size)) => {
+            resource.handle = pack.handle.clone();
+            resource.texture_array = Some(images.add(texture_array));
+            resource.texture_array_size = size;
+        }
+        Err(err) => {
+            error!("{}", err);
+            exit.send(AppExit);
+        }
+    }
+}
+
+/// Texture array with one layer per sheet of the pack and its size
+fn icon_texture_array(
+    pack: &IconPack,
+    assets: &Assets<IconSheetAsset>,
+    images: &Assets<Image>,
+) -> Result<(Image, Vec2), AssetError> {
+    let IconSheetAsset(sheets) =
+        assets
+            .get(&pack.handle)
+            .ok_or_else(|| AssetError::PackNotLoaded {
+                pack: pack.name.clone(),
+            })?;
+    // a sheet that is left out would shift the layers of the sheets after it
     let textures = sheets
         .iter()
-        .flat_map(|sheet| images.get(sheet.handle.id()))
-        .collect::<Vec<&Image>>();
+        .enumerate()
+        .map(|(index, sheet)| {
+            let texture = images.get(sheet.handle.id());
+            // the layers of the array share one format
+            let texture = texture.and_then(|texture| {
+                if texture.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb {
+                    Some(texture.clone())
+                } else {
+                    texture.convert(TextureFormat::Rgba8UnormSrgb)
+                }
+            });
+            texture.ok_or_else(|| AssetError::BrokenSheet {
+                pack: pack.name.clone(),
+                sheet: sheet
+                    .handle
+                    .path()
+                    .map_or_else(|| format!("#{}", index), ToString::to_string),
+            })
+        })
+        .collect::<Result<Vec<Image>, _>>()?;
 
+    // every layer is as large as the largest sheet, smaller sheets are padded
+    let (Some(width), Some(height)) = (
+        textures.iter().map(|texture| texture.width()).max(),
+        textures.iter().map(|texture| texture.height()).max(),
+    ) else {
+        return Err(AssetError::EmptyPack {
+            pack: pack.name.clone(),
+        });
+    };
+    // the mip chain ends before the gutter between the tiles is used up, the sampler
+    // would read the neighbouring tiles after it
+    let gap = sheets
+        .iter()
+        .map(|sheet| sheet.gap)
+        .min()
+        .unwrap_or_default();
+    let mip_level_count = mipmaps::mip_level_count(width, height, gap);
+    let layer_len = (width * height * 4) as usize;
     let mut data = Vec::new();
-    textures.iter().for_each(|texture| {
-        // println!("texture format: {:?}", texture.texture_descriptor);
-        data.extend_from_slice(&texture.data);
-    });
+    for (texture, sheet) in textures.iter().zip(sheets) {
+        // the layers are stored one after the other, each with all its mip levels
+        let mut layer = vec![0; layer_len];
+        let row_len = (texture.width() * 4) as usize;
+        for (y, row) in texture.data.chunks_exact(row_len).enumerate() {
+            let start = y * (width * 4) as usize;
+            layer[start..start + row_len].copy_from_slice(row);
+        }
+        // the mip levels are averaged per tile, so the gutter stays transparent
+        let rects = sheet
+            .tiles
+            .iter()
+            .map(|icon| {
+                let min = UVec2::new(icon.x as u32, icon.y as u32);
+                let size = UVec2::new(sheet.tile_width as u32, sheet.tile_height as u32);
+                URect::from_corners(min, min + size)
+            })
+            .collect::<Vec<_>>();
+        mipmaps::append_mip_chain(&mut layer, width, height, mip_level_count, &rects);
+        data.extend_from_slice(&layer);
+    }
 
-    // create array texture:
-    let mut texture_array = Image::new(
-        Extent3d {
-            width: width as u32,
-            height: height as u32,
-            depth_or_array_layers: textures.len() as u32,
-        },
-        TextureDimension::D2,
+    // create array texture, it is sampled with the samplers of the icon pipeline:
+    let mut texture_array = Image {
         data,
-        TextureFormat::Rgba8UnormSrgb,
-        RenderAssetUsages::RENDER_WORLD, // unload / only loaded in render world
-    );
-    texture_array.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
-        mag_filter: ImageFilterMode::Nearest,
-        min_filter: ImageFilterMode::Nearest,
-        mipmap_filter: ImageFilterMode::Nearest,
-        // lod_min_clamp: (),
-        // lod_max_clamp: (),
+        asset_usage: RenderAssetUsages::RENDER_WORLD, // unload / only loaded in render world
+        ..Default::default()
+    };
+    texture_array.texture_descriptor.size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: textures.len() as u32,
+    };
+    texture_array.texture_descriptor.dimension = TextureDimension::D2;
+    texture_array.texture_descriptor.format = TextureFormat::Rgba8UnormSrgb;
+    texture_array.texture_descriptor.mip_level_count = mip_level_count;
+    // packs with a single sheet are still an array
+    texture_array.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
         ..Default::default()
     });
-    let texture_array_handle = images.add(texture_array);
-    resource.texture_array = Some(texture_array_handle);
-
-    state.set(GameState::GameLoading);
+    Ok((texture_array, Vec2::new(width as f32, height as f32)))
 }
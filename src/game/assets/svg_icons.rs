@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::BoxedFuture,
+};
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+
+use super::icons::{AssetError, Icon, IconSheet, IconSheetAsset};
+
+fn default_tile_size() -> u32 {
+    64
+}
+
+fn default_sheet_size() -> u32 {
+    2048
+}
+
+fn default_gap() -> u32 {
+    4
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SvgIconFile {
+    /// Name used to spawn the icon, defaults to the file name without extension
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Path of the SVG, relative to the bundle file
+    pub file: String,
+}
+
+/// Bundle of SVG files as represented in the JSON file, rasterised when loaded
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SvgIconBundleFile {
+    #[serde(default = "default_tile_size")]
+    pub tile_size: u32,
+    #[serde(default = "default_sheet_size")]
+    pub sheet_size: u32,
+    /// Transparent pixels between tiles, half of it on each side
+    #[serde(default = "default_gap")]
+    pub gap: u32,
+    pub icons: Vec<SvgIconFile>,
+}
+
+#[derive(Default)]
+pub struct SvgIconSheetLoader;
+
+/// Render the SVG centered into a tile of the sheet, keeping its aspect ratio
+fn rasterise_svg(
+    bytes: &[u8],
+    tile_size: u32,
+    x: u32,
+    y: u32,
+    sheet: &mut tiny_skia::Pixmap,
+) -> Result<(), usvg::Error> {
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())?;
+    let size = tree.size();
+    let scale = tile_size as f32 / size.width().max(size.height());
+    let offset_x = x as f32 + (tile_size as f32 - size.width() * scale) / 2.0;
+    let offset_y = y as f32 + (tile_size as f32 - size.height() * scale) / 2.0;
+    let transform =
+        tiny_skia::Transform::from_scale(scale, scale).post_translate(offset_x, offset_y);
+    resvg::render(&tree, transform, &mut sheet.as_mut());
+    Ok(())
+}
+
+fn sheet_image(sheet: &tiny_skia::Pixmap) -> Image {
+    // tiny-skia works with premultiplied alpha, the PNG sheets are not
+    let data = sheet
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    Image::new(
+        Extent3d {
+            width: sheet.width(),
+            height: sheet.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        // the sheets are copied into the texture array in the main world
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+impl AssetLoader for SvgIconSheetLoader {
+    type Asset = IconSheetAsset;
+    type Settings = ();
+    type Error = AssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let bundle = serde_json::from_slice::<SvgIconBundleFile>(&bytes)?;
+
+            let step = bundle.tile_size + bundle.gap;
+            let tiles_per_row = bundle.sheet_size / step;
+            if bundle.tile_size == 0 || tiles_per_row == 0 {
+                return Err(anyhow::anyhow!(
+                    "Tile size {} does not fit into sheet size {}",
+                    bundle.tile_size,
+                    bundle.sheet_size
+                )
+                .into());
+            }
+            let tiles_per_sheet = (tiles_per_row * tiles_per_row) as usize;
+            let directory = load_context
+                .path()
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+
+            let mut sheets = Vec::new();
+            for (index, chunk) in bundle.icons.chunks(tiles_per_sheet).enumerate() {
+                let mut pixmap = tiny_skia::Pixmap::new(bundle.sheet_size, bundle.sheet_size)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid sheet size {}", bundle.sheet_size))?;
+                let mut tiles = Vec::new();
+                for (i, icon) in chunk.iter().enumerate() {
+                    let path = directory.join(&icon.file);
+                    let x = (i as u32 % tiles_per_row) * step + bundle.gap / 2;
+                    let y = (i as u32 / tiles_per_row) * step + bundle.gap / 2;
+                    let svg = load_context.read_asset_bytes(path.clone()).await?;
+                    rasterise_svg(&svg, bundle.tile_size, x, y, &mut pixmap)
+                        .map_err(|err| anyhow::anyhow!("Failed rasterising {:?}: {}", path, err))?;
+
+                    let name = icon.name.clone().unwrap_or_else(|| {
+                        path.file_stem()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                            .unwrap_or_default()
+                    });
+                    tiles.push(Icon {
+                        name,
+                        x: x as usize,
+                        y: y as usize,
+                    });
+                }
+
+                info!("Rasterised icon sheet {} with {} icons", index, tiles.len());
+                let handle =
+                    load_context.add_labeled_asset(format!("sheet{}", index), sheet_image(&pixmap));
+                sheets.push(IconSheet {
+                    handle,
+                    width: bundle.sheet_size as usize,
+                    height: bundle.sheet_size as usize,
+                    tile_width: bundle.tile_size as usize,
+                    tile_height: bundle.tile_size as usize,
+                    gap: bundle.gap as usize,
+                    tiles,
+                });
+            }
+            Ok(IconSheetAsset(sheets))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["svg.icon.json"]
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::{Deserialize, Serialize};
+
+use super::icons::AssetError;
+use crate::game::theme::{Theme, ThemeColor};
+
+/// ThemeFile as represented in the JSON file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThemeFile {
+    pub name: String,
+    /// Hex colors by role, missing roles keep the color of the dark theme
+    pub colors: HashMap<ThemeColor, String>,
+}
+
+/// Themes of the theme switcher, in the order of the file
+#[derive(Asset, TypePath, Debug)]
+pub struct ThemeListAsset(pub Vec<Theme>);
+
+#[derive(Default)]
+pub struct ThemeListLoader;
+
+impl AssetLoader for ThemeListLoader {
+    type Asset = ThemeListAsset;
+    type Settings = ();
+    type Error = AssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let files = serde_json::from_slice::<Vec<ThemeFile>>(&bytes)?;
+            if files.is_empty() {
+                return Err(anyhow::anyhow!("Theme file lists no themes").into());
+            }
+            let themes = files
+                .into_iter()
+                .map(|file| {
+                    let name = file.name.clone();
+                    Theme::from_hex(file.name, &file.colors)
+                        .map_err(|err| anyhow::anyhow!("Theme {:?} has an {}", name, err))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ThemeListAsset(themes))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["themes.json"]
+    }
+}
@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use bevy::sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle};
+use serde::{Deserialize, Serialize};
+
+use super::camera::{CameraTag, CAMERA_LAYER, CAMERA_Z_BACKGROUND};
+use super::icons::IconFollowers;
+use super::settings::SettingsResource;
+use super::states::GameState;
+use super::theme::{Theme, ThemeColor, ThemeResource};
+use super::world::{WorldBoundaryResource, WorldLayoutResource};
+
+pub const MAX_BACKGROUND_LAYERS: usize = 4;
+const MAX_DROPZONES: usize = 8;
+
+/// Seconds for the dropzone highlight to fade in or out
+const HIGHLIGHT_FADE: f32 = 0.3;
+
+/// Parallax layer of the background, `parallax` is how much the layer moves
+/// with the world, 0 stays fixed on screen and 1 moves like the world
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BackgroundLayer {
+    /// One star per cell of `spacing` units at a random position
+    #[serde(rename_all = "camelCase")]
+    Stars {
+        parallax: f32,
+        spacing: f32,
+        size: f32,
+        /// Defaults to the stars color of the theme
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Grid {
+        parallax: f32,
+        spacing: f32,
+        width: f32,
+        /// Defaults to the grid color of the theme
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+    },
+}
+
+/// Look of the background below the world grid, colors that are not set
+/// follow the theme
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BackgroundStyle {
+    /// Fill of the world area
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    pub layers: Vec<BackgroundLayer>,
+    /// Strength of the animated pattern, 0 turns it off
+    pub pattern: f32,
+    pub pattern_scale: f32,
+    pub pattern_speed: f32,
+    /// Pulsing border around the dropzones while the player has followers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_color: Option<String>,
+}
+
+impl Default for BackgroundStyle {
+    fn default() -> Self {
+        Self {
+            color: None,
+            layers: vec![
+                BackgroundLayer::Stars {
+                    parallax: 0.2,
+                    spacing: 96.0,
+                    size: 3.0,
+                    color: None,
+                },
+                BackgroundLayer::Stars {
+                    parallax: 0.5,
+                    spacing: 192.0,
+                    size: 5.0,
+                    color: None,
+                },
+            ],
+            pattern: 0.5,
+            pattern_scale: 0.002,
+            pattern_speed: 0.2,
+            highlight_color: None,
+        }
+    }
+}
+
+impl BackgroundStyle {
+    /// Returns a description of the first invalid value
+    pub fn validate(&self) -> Result<(), String> {
+        if self.layers.len() > MAX_BACKGROUND_LAYERS {
+            return Err(format!(
+                "at most {} background layers are supported",
+                MAX_BACKGROUND_LAYERS
+            ));
+        }
+        let layer_colors = self.layers.iter().map(|layer| match layer {
+            BackgroundLayer::Stars { color, .. } | BackgroundLayer::Grid { color, .. } => color,
+        });
+        for color in [&self.color, &self.highlight_color]
+            .into_iter()
+            .chain(layer_colors)
+            .flatten()
+        {
+            if Color::hex(color).is_err() {
+                return Err(format!("invalid background color {:?}", color));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, ShaderType)]
+struct BackgroundLayerUniform {
+    color: Vec4,
+    /// x = kind (0 stars, 1 grid), y = parallax, z = spacing, w = size or width
+    params: Vec4,
+}
+
+#[derive(Debug, Clone, Default, ShaderType)]
+struct BackgroundUniform {
+    /// xy = camera position, z = time, w = dropzone highlight
+    camera: Vec4,
+    /// xy = min, zw = max corner of the world
+    bounds: Vec4,
+    color: Vec4,
+    highlight_color: Vec4,
+    /// x = strength, y = scale, z = speed, w = world wraps around
+    pattern: Vec4,
+    /// x = layers, y = dropzones
+    counts: UVec4,
+    layers: [BackgroundLayerUniform; MAX_BACKGROUND_LAYERS],
+    /// xy = min, zw = max corner
+    dropzones: [Vec4; MAX_DROPZONES],
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+struct BackgroundMaterial {
+    #[uniform(0)]
+    uniform: BackgroundUniform,
+}
+
+impl Material2d for BackgroundMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "background.wgsl".into()
+    }
+}
+
+#[derive(Component)]
+struct BackgroundTag;
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<BackgroundMaterial>::default());
+        app.add_systems(Startup, spawn_background_system);
+        app.add_systems(
+            Update,
+            (
+                update_background_style_system.run_if(
+                    resource_changed::<WorldLayoutResource>
+                        .or_else(resource_changed::<WorldBoundaryResource>)
+                        .or_else(resource_changed::<ThemeResource>),
+                ),
+                update_background_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The color of the level, or of the theme if the level does not set it
+fn linear_color(hex: Option<&str>, theme: &Theme, fallback: ThemeColor) -> Vec4 {
+    match hex {
+        Some(hex) => Vec4::from(Color::hex(hex).unwrap_or(Color::NONE).as_linear_rgba_f32()),
+        None => theme.linear(fallback),
+    }
+}
+
+fn spawn_background_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<BackgroundMaterial>>,
+) {
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(Rectangle::new(1.0, 1.0)).into(),
+            material: materials.add(BackgroundMaterial::default()),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, CAMERA_Z_BACKGROUND - 2.0)),
+            ..Default::default()
+        },
+        RenderLayers::layer(CAMERA_LAYER),
+        NoFrustumCulling,
+        BackgroundTag,
+    ));
+}
+
+/// Style of the current level, only changes with the level and the theme
+fn update_background_style_system(
+    background: Query<&Handle<BackgroundMaterial>, With<BackgroundTag>>,
+    mut materials: ResMut<Assets<BackgroundMaterial>>,
+    layouts: Res<WorldLayoutResource>,
+    boundaries: Res<WorldBoundaryResource>,
+    theme: Res<ThemeResource>,
+) {
+    let Some(material) = background
+        .get_single()
+        .ok()
+        .and_then(|handle| materials.get_mut(handle))
+    else {
+        return;
+    };
+    let style = &layouts.current().background;
+    let theme = theme.current();
+    let uniform = &mut material.uniform;
+
+    uniform.bounds = Vec4::new(
+        boundaries.bounds_min.x,
+        boundaries.bounds_min.y,
+        boundaries.bounds_max.x,
+        boundaries.bounds_max.y,
+    );
+    uniform.color = linear_color(style.color.as_deref(), theme, ThemeColor::Background);
+    uniform.highlight_color =
+        linear_color(style.highlight_color.as_deref(), theme, ThemeColor::Accent);
+    uniform.pattern = Vec4::new(
+        style.pattern,
+        style.pattern_scale,
+        style.pattern_speed,
+        if boundaries.wrap { 1.0 } else { 0.0 },
+    );
+
+    uniform.layers = Default::default();
+    for (layer, uniform_layer) in style.layers.iter().zip(uniform.layers.iter_mut()) {
+        *uniform_layer = match layer {
+            BackgroundLayer::Stars {
+                parallax,
+                spacing,
+                size,
+                color,
+            } => BackgroundLayerUniform {
+                color: linear_color(color.as_deref(), theme, ThemeColor::BackgroundStars),
+                params: Vec4::new(0.0, *parallax, *spacing, *size),
+            },
+            BackgroundLayer::Grid {
+                parallax,
+                spacing,
+                width,
+                color,
+            } => BackgroundLayerUniform {
+                color: linear_color(color.as_deref(), theme, ThemeColor::Grid),
+                params: Vec4::new(1.0, *parallax, *spacing, *width),
+            },
+        };
+    }
+    uniform.counts.x = style.layers.len().min(MAX_BACKGROUND_LAYERS) as u32;
+}
+
+/// Keeps the background covering the view and animates it
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_background_system(
+    mut background: Query<
+        (&Handle<BackgroundMaterial>, &mut Transform),
+        (With<BackgroundTag>, Without<CameraTag>),
+    >,
+    camera: Query<(&Transform, &OrthographicProjection), With<CameraTag>>,
+    mut materials: ResMut<Assets<BackgroundMaterial>>,
+    boundaries: Res<WorldBoundaryResource>,
+    followers: Res<IconFollowers>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+    settings: Res<SettingsResource>,
+    mut highlight: Local<f32>,
+) {
+    let (Ok((handle, mut transform)), Ok((camera_transform, projection))) =
+        (background.get_single_mut(), camera.get_single())
+    else {
+        return;
+    };
+
+    // cover the whole view, the camera might be rotated
+    let view_size = projection.area.size();
+    let diagonal = view_size.length();
+    transform.translation.x = camera_transform.translation.x;
+    transform.translation.y = camera_transform.translation.y;
+    transform.scale = Vec3::new(diagonal, diagonal, 1.0);
+
+    let carrying = *state == GameState::GameRunning && !followers.followers.is_empty();
+    let target = if carrying { 1.0 } else { 0.0 };
+    let step = time.delta_seconds() / HIGHLIGHT_FADE;
+    *highlight = if *highlight < target {
+        (*highlight + step).min(target)
+    } else {
+        (*highlight - step).max(target)
+    };
+
+    let Some(material) = materials.get_mut(handle) else {
+        return;
+    };
+    let uniform = &mut material.uniform;
+    uniform.camera = Vec4::new(
+        camera_transform.translation.x,
+        camera_transform.translation.y,
+        // a still time stops the waves, the twinkling and the highlight pulse
+        if settings.reduced_motion {
+            0.0
+        } else {
+            time.elapsed_seconds_wrapped()
+        },
+        *highlight,
+    );
+    // dropzones drift and shrink
+    for (dropzone, uniform_dropzone) in boundaries
+        .dropzones
+        .iter()
+        .zip(uniform.dropzones.iter_mut())
+    {
+        *uniform_dropzone = Vec4::new(
+            dropzone.min.x,
+            dropzone.min.y,
+            dropzone.max.x,
+            dropzone.max.y,
+        );
+    }
+    uniform.counts.y = boundaries.dropzones.len().min(MAX_DROPZONES) as u32;
+}
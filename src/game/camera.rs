@@ -1,26 +1,42 @@
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
-use bevy::window::WindowResized;
+use bevy::window::{PrimaryWindow, WindowResized};
 
-use super::icons::{IconPlayerController, IconTransform};
+use super::controls::VirtualInputResource;
+use super::icons::events::{IconCaptureEvent, PlayerDamageEvent};
+use super::icons::{IconPlayerController, IconPlayerHeading, IconTransform, IconVelocity};
+use super::postprocess::PostProcessSettings;
+use super::settings::{CameraLookAhead, ControlScheme, SettingsResource};
 use super::states::GameState;
 use super::world::WorldBoundaryResource;
 
 pub const CAMERA_LAYER: u8 = 1;
 pub const CAMERA_LAYER_UI: u8 = 2;
+/// Used by the [`CameraTag`] camera in the level editor, only the editor gizmos are drawn
+pub const CAMERA_LAYER_EDITOR: u8 = 3;
 
 pub const CAMERA_Z_BACKGROUND: f32 = -10.0;
 pub const CAMERA_Z_ICONS: f32 = 0.0;
 pub const CAMERA_Z_VFX: f32 = 1.0;
 
+/// Longest zoom-to-cursor offset as a fraction of the smaller window side, keeps the player
+/// on screen
+const ZOOM_OFFSET_MAX: f32 = 0.4;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_cameras);
         app.add_systems(OnEnter(GameState::MainMenu), make_camera_visible);
-        app.add_systems(OnEnter(GameState::GameRunning), enter_game_running_system);
+        app.add_systems(
+            OnTransition {
+                from: GameState::MainMenu,
+                to: GameState::GameRunning,
+            },
+            start_fly_in_system,
+        );
 
         app.add_systems(
             Update,
@@ -29,7 +45,12 @@ impl Plugin for CameraPlugin {
 
         app.add_systems(
             Update,
-            (camera_zoom_system, camera_follow_player_icon_system)
+            (
+                camera_zoom_system,
+                camera_trauma_system,
+                camera_follow_player_icon_system,
+            )
+                .chain()
                 .run_if(in_state(GameState::GameRunning)),
         );
     }
@@ -38,6 +59,49 @@ impl Plugin for CameraPlugin {
 #[derive(Component)]
 pub struct CameraTag;
 
+/// Smoothed camera focus, zoom and screen shake state of the [`CameraTag`] camera
+#[derive(Component, Debug)]
+pub struct CameraRig {
+    /// Point the camera is centered on, before shake is applied
+    pub focus: Vec2,
+    /// Shake intensity between 0 and 1, decays over time
+    pub trauma: f32,
+    /// Projection scale the zoom eases toward
+    pub target_scale: f32,
+    /// Last zoom came from the mouse wheel and is anchored at the cursor
+    zoom_to_cursor: bool,
+    /// Added to the focus by zooming at the cursor, kept while following the player
+    zoom_offset: Vec2,
+    fly_in: Option<CameraFlyIn>,
+}
+
+/// Flight from the menu overview to the player when a game starts
+#[derive(Debug)]
+struct CameraFlyIn {
+    from_focus: Vec2,
+    from_scale: f32,
+    elapsed: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            focus: Vec2::ZERO,
+            trauma: 0.0,
+            target_scale: 1.0,
+            zoom_to_cursor: false,
+            zoom_offset: Vec2::ZERO,
+            fly_in: None,
+        }
+    }
+}
+
+impl CameraRig {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
 #[derive(Component)]
 pub struct UiCameraTag;
 
@@ -53,6 +117,7 @@ fn setup_cameras(mut commands: Commands) {
         },
         RenderLayers::layer(CAMERA_LAYER),
         CameraTag,
+        CameraRig::default(),
     ));
 
     commands.spawn((
@@ -67,11 +132,21 @@ fn setup_cameras(mut commands: Commands) {
         },
         RenderLayers::layer(CAMERA_LAYER_UI),
         UiCameraTag,
+        PostProcessSettings::default(),
     ));
 }
 
+#[allow(clippy::type_complexity)]
 fn make_camera_visible(
-    mut query: Query<(&mut Transform, &mut Camera, &mut OrthographicProjection), With<CameraTag>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut Camera,
+            &mut OrthographicProjection,
+            &mut CameraRig,
+        ),
+        With<CameraTag>,
+    >,
     window: Query<&Window>,
     boundaries: Res<WorldBoundaryResource>,
 ) {
@@ -80,20 +155,34 @@ fn make_camera_visible(
     let world_size = boundaries.size();
     let max_scale = (world_size.x / window.width()).max(world_size.y / window.height());
 
-    let (mut transform, mut camera, mut projection) = query.single_mut();
+    let (mut transform, mut camera, mut projection, mut rig) = query.single_mut();
+    *rig = CameraRig {
+        target_scale: max_scale,
+        ..Default::default()
+    };
 
     camera.is_active = true;
     projection.scale = max_scale;
     transform.translation = Vec3::ZERO;
+    transform.rotation = Quat::IDENTITY;
 }
 
-fn enter_game_running_system(mut query: Query<&mut OrthographicProjection, With<CameraTag>>) {
-    let mut projection = query.single_mut();
-    projection.scale = 1.0;
+fn start_fly_in_system(
+    mut query: Query<(&OrthographicProjection, &mut CameraRig), With<CameraTag>>,
+) {
+    let (projection, mut rig) = query.single_mut();
+    rig.target_scale = 1.0;
+    let from_focus = rig.focus + rig.zoom_offset;
+    rig.zoom_offset = Vec2::ZERO;
+    rig.fly_in = Some(CameraFlyIn {
+        from_focus,
+        from_scale: projection.scale,
+        elapsed: 0.0,
+    });
 }
 
 fn resize_menu_camera(
-    mut query: Query<(&mut Camera, &mut OrthographicProjection), With<CameraTag>>,
+    mut query: Query<(&mut Camera, &mut OrthographicProjection, &mut CameraRig), With<CameraTag>>,
     window: Query<&Window>,
     boundaries: Res<WorldBoundaryResource>,
     mut resize_reader: EventReader<WindowResized>,
@@ -104,55 +193,211 @@ fn resize_menu_camera(
         let world_size = boundaries.size();
         let max_scale = (world_size.x / window.width()).max(world_size.y / window.height());
 
-        let (mut camera, mut projection) = query.single_mut();
+        let (mut camera, mut projection, mut rig) = query.single_mut();
 
         camera.is_active = true;
         projection.scale = max_scale;
+        rig.target_scale = max_scale;
     }
 }
 
+fn clamp_zoom(scale: f32, window: &Window, boundaries: &WorldBoundaryResource) -> f32 {
+    let world_size = boundaries.size();
+    let max_scale = (world_size.x / window.width()).max(world_size.y / window.height());
+
+    scale.min(max_scale).max(1.0)
+}
+
+#[allow(clippy::type_complexity)]
 fn camera_zoom_system(
-    mut query: Query<&mut OrthographicProjection, With<Camera>>,
-    window: Query<&Window>,
+    mut query: Query<(&Transform, &mut OrthographicProjection, &mut CameraRig), With<CameraTag>>,
+    window: Query<&Window, With<PrimaryWindow>>,
     mut scroll_events: EventReader<MouseWheel>,
     boundaries: Res<WorldBoundaryResource>,
+    virtual_input: Res<VirtualInputResource>,
+    settings: Res<SettingsResource>,
+    time: Res<Time>,
 ) {
+    let window = window.single();
+    let (transform, mut projection, mut rig) = query.single_mut();
+
     for event in scroll_events.read() {
-        for mut projection in query.iter_mut() {
-            let window = window.single();
+        let event_y = if event.y > 0.0 {
+            1.0
+        } else if event.y < 0.0 {
+            -1.0
+        } else {
+            0.0
+        };
 
-            const SCALE_FACTOR: f32 = 0.3;
+        rig.target_scale *= settings.camera_zoom_step.powf(-event_y);
+        rig.zoom_to_cursor = settings.camera_zoom_to_cursor;
+    }
 
-            let event_y = if event.y > 0.0 {
-                1.0
-            } else if event.y < 0.0 {
-                -1.0
-            } else {
-                0.0
-            };
+    // pinch-to-zoom
+    if virtual_input.zoom != 1.0 {
+        rig.target_scale *= virtual_input.zoom;
+        rig.zoom_to_cursor = false;
+    }
+
+    rig.target_scale = clamp_zoom(rig.target_scale, window, &boundaries);
 
-            projection.scale += (event_y * SCALE_FACTOR) * -1.0;
+    // the fly-in animates the zoom on its own
+    if rig.fly_in.is_some() {
+        return;
+    }
 
-            let world_size = boundaries.size();
-            let max_scale = (world_size.x / window.width()).max(world_size.y / window.height());
+    let previous_scale = projection.scale;
+    projection.scale = if settings.camera_zoom_speed > 0.0 {
+        let t = 1.0 - (-settings.camera_zoom_speed * time.delta_seconds()).exp();
+        previous_scale + (rig.target_scale - previous_scale) * t
+    } else {
+        rig.target_scale
+    };
 
-            if projection.scale < 1.0 {
-                projection.scale = 1.0;
-            }
-            if projection.scale > max_scale {
-                projection.scale = max_scale;
-            }
+    // move the focus so the world position under the cursor stays in place
+    if rig.zoom_to_cursor {
+        if let Some(cursor) = window.cursor_position() {
+            let offset = Vec2::new(
+                cursor.x - window.width() / 2.0,
+                window.height() / 2.0 - cursor.y,
+            );
+            let offset = (transform.rotation * offset.extend(0.0)).truncate();
+            rig.zoom_offset += offset * (previous_scale - projection.scale);
         }
     }
+    let max_offset = window.width().min(window.height()) * ZOOM_OFFSET_MAX * projection.scale;
+    rig.zoom_offset = rig.zoom_offset.clamp_length_max(max_offset);
+}
+
+fn camera_trauma_system(
+    mut query: Query<&mut CameraRig, With<CameraTag>>,
+    mut damage_events: EventReader<PlayerDamageEvent>,
+    mut capture_events: EventReader<IconCaptureEvent>,
+    settings: Res<SettingsResource>,
+) {
+    let mut rig = query.single_mut();
+
+    for _ in damage_events.read() {
+        rig.add_trauma(settings.camera_shake_damage_trauma);
+    }
+
+    // all followers are captured at once, only shake for large drop-offs
+    let n_captured = capture_events.read().count() as u32;
+    if n_captured >= settings.camera_shake_capture_threshold {
+        rig.add_trauma(n_captured as f32 * settings.camera_shake_capture_trauma);
+    }
+}
+
+fn ease_in_out(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Smooth noise between -1 and 1, `seed` selects an independent curve
+fn shake_noise(time: f32, seed: f32) -> f32 {
+    ((time * 23.0 + seed).sin() + (time * 37.0 + seed * 2.0).sin() * 0.5) / 1.5
 }
 
+#[allow(clippy::type_complexity)]
 fn camera_follow_player_icon_system(
-    player_icon: Query<&IconTransform, With<IconPlayerController>>,
-    mut query: Query<&mut Transform, With<CameraTag>>,
+    player_icon: Query<
+        (&IconTransform, &IconVelocity, &IconPlayerHeading),
+        With<IconPlayerController>,
+    >,
+    mut query: Query<
+        (&mut Transform, &mut OrthographicProjection, &mut CameraRig),
+        With<CameraTag>,
+    >,
+    settings: Res<SettingsResource>,
+    boundaries: Res<WorldBoundaryResource>,
+    time: Res<Time>,
 ) {
-    if let Ok(IconTransform { position, .. }) = player_icon.get_single() {
-        let mut camera = query.single_mut();
-        camera.translation.x = position.x;
-        camera.translation.y = position.y;
+    let Ok((IconTransform { position, rotation }, velocity, heading)) = player_icon.get_single()
+    else {
+        return;
+    };
+    let (mut camera, mut projection, mut rig) = query.single_mut();
+    let dt = time.delta_seconds();
+
+    // look ahead of the player, scaled by its speed when looking along the velocity
+    let look_ahead = match settings.camera_look_ahead {
+        CameraLookAhead::Off => Vec2::ZERO,
+        CameraLookAhead::Velocity => {
+            let max_speed = settings.controller_max_speed * dt;
+            if velocity.0.length() > 0.0 && max_speed > 0.0 {
+                velocity.0.normalize() * (velocity.0.length() / max_speed).min(1.0)
+            } else {
+                Vec2::ZERO
+            }
+        }
+        CameraLookAhead::Aim => {
+            let r = std::f32::consts::PI / 2.0;
+            Vec2::new((rotation + r).cos(), (rotation + r).sin())
+        }
+    };
+    let target = *position + look_ahead * settings.camera_look_ahead_distance;
+
+    // keep the focus next to the player when it wraps to the opposite side of the world
+    rig.focus = target + boundaries.delta(target, rig.focus);
+
+    if let Some(mut fly_in) = rig.fly_in.take() {
+        fly_in.elapsed += dt;
+        let t = if settings.camera_fly_in_duration > 0.0 && !settings.reduced_motion {
+            fly_in.elapsed / settings.camera_fly_in_duration
+        } else {
+            1.0
+        };
+        if t < 1.0 {
+            // the player might already move, so fly toward its current position
+            let t = ease_in_out(t);
+            rig.focus = fly_in.from_focus.lerp(target, t);
+            projection.scale = fly_in.from_scale + (rig.target_scale - fly_in.from_scale) * t;
+            rig.fly_in = Some(fly_in);
+        } else {
+            rig.focus = target;
+            projection.scale = rig.target_scale;
+        }
+    }
+
+    // only follow once the target leaves the dead zone around the focus
+    let offset = target - rig.focus;
+    let distance = offset.length();
+    if rig.fly_in.is_none() && distance > settings.camera_dead_zone {
+        let desired = target - offset / distance * settings.camera_dead_zone;
+        rig.focus = if settings.camera_damping > 0.0 {
+            rig.focus
+                .lerp(desired, 1.0 - (-settings.camera_damping * dt).exp())
+        } else {
+            desired
+        };
     }
+
+    rig.trauma = (rig.trauma - settings.camera_shake_decay * dt).max(0.0);
+    let shake_enabled = settings.camera_shake && !settings.reduced_motion;
+    let (shake_offset, shake_angle) = if shake_enabled && rig.trauma > 0.0 {
+        let shake = rig.trauma * rig.trauma;
+        let t = time.elapsed_seconds();
+        (
+            Vec2::new(shake_noise(t, 0.0), shake_noise(t, 10.0))
+                * settings.camera_shake_max_offset
+                * shake,
+            shake_noise(t, 20.0) * settings.camera_shake_max_angle * shake,
+        )
+    } else {
+        (Vec2::ZERO, 0.0)
+    };
+
+    // the dead zone follows the focus without the zoom offset, so it doesn't pull it back
+    let center = rig.focus + rig.zoom_offset;
+    camera.translation.x = center.x + shake_offset.x;
+    camera.translation.y = center.y + shake_offset.y;
+
+    // with the tank scheme forward can always be up on screen
+    let base_rotation =
+        if settings.controller_scheme == ControlScheme::Tank && settings.controller_rotate_camera {
+            heading.0
+        } else {
+            0.0
+        };
+    camera.rotation = Quat::from_rotation_z(base_rotation + shake_angle);
 }
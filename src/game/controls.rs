@@ -0,0 +1,483 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use super::settings::{ControlScheme, SettingsResource};
+
+/// File the user bindings are persisted to (native builds only)
+#[cfg(not(target_arch = "wasm32"))]
+const BINDINGS_FILE: &str = "iconwars.controls.json";
+
+/// Number of bindings each action can have (primary / secondary)
+pub const BINDING_SLOTS: usize = 2;
+
+/// Keys that can be bound to an action, also used to parse persisted bindings.
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Quote,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::Backquote,
+    KeyCode::Backslash,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+];
+
+/// Keys that always trigger their action on top of the bindings, escape can't be
+/// bound since it cancels rebinding
+const FIXED_KEYS: &[(InputAction, KeyCode)] = &[(InputAction::Pause, KeyCode::Escape)];
+
+const BINDABLE_MOUSE_BUTTONS: &[MouseButton] =
+    &[MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Everything the player can do, independent of the physical input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Shoot,
+    Pause,
+    ToggleDebug,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 7] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::Shoot,
+        InputAction::Pause,
+        InputAction::ToggleDebug,
+    ];
+
+    fn default_bindings(&self) -> [Option<InputBinding>; BINDING_SLOTS] {
+        use InputBinding::*;
+        match self {
+            InputAction::MoveUp => [Some(Key(KeyCode::ArrowUp)), Some(Key(KeyCode::KeyW))],
+            InputAction::MoveDown => [Some(Key(KeyCode::ArrowDown)), Some(Key(KeyCode::KeyS))],
+            InputAction::MoveLeft => [Some(Key(KeyCode::ArrowLeft)), Some(Key(KeyCode::KeyA))],
+            InputAction::MoveRight => [Some(Key(KeyCode::ArrowRight)), Some(Key(KeyCode::KeyD))],
+            InputAction::Shoot => [Some(Key(KeyCode::Space)), Some(Mouse(MouseButton::Left))],
+            // escape always pauses, but on web it also leaves fullscreen, so P is bound as well
+            InputAction::Pause => [Some(Key(KeyCode::KeyP)), None],
+            InputAction::ToggleDebug => [Some(Key(KeyCode::KeyO)), None],
+        }
+    }
+
+    /// Key that triggers the action whatever it is bound to
+    pub fn fixed_key(&self) -> Option<KeyCode> {
+        FIXED_KEYS
+            .iter()
+            .find(|(action, _)| action == self)
+            .map(|(_, key)| *key)
+    }
+}
+
+/// A physical input that triggers an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl InputBinding {
+    /// Returns true if this input can be bound and persisted
+    pub fn is_bindable(&self) -> bool {
+        match self {
+            InputBinding::Key(key) => BINDABLE_KEYS.contains(key),
+            InputBinding::Mouse(button) => BINDABLE_MOUSE_BUTTONS.contains(button),
+        }
+    }
+
+    /// Human readable name as shown in the menus, e.g. `W`, `Up` or `Left Click`
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(key) => {
+                let name = format!("{:?}", key);
+                ["Key", "Digit", "Arrow"]
+                    .iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .map(|name| name.to_string())
+                    .unwrap_or(name)
+            }
+            InputBinding::Mouse(button) => format!("{:?} Click", button),
+        }
+    }
+}
+
+impl From<InputBinding> for String {
+    fn from(binding: InputBinding) -> Self {
+        match binding {
+            InputBinding::Key(key) => format!("{:?}", key),
+            InputBinding::Mouse(button) => format!("Mouse{:?}", button),
+        }
+    }
+}
+
+impl TryFrom<String> for InputBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        // files of older versions bound the fixed keys, they are dropped when loaded
+        BINDABLE_KEYS
+            .iter()
+            .chain(FIXED_KEYS.iter().map(|(_, key)| key))
+            .map(|key| InputBinding::Key(*key))
+            .chain(
+                BINDABLE_MOUSE_BUTTONS
+                    .iter()
+                    .map(|button| InputBinding::Mouse(*button)),
+            )
+            .find(|binding| String::from(*binding) == value)
+            .ok_or_else(|| format!("Unknown input binding: {}", value))
+    }
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct InputBindingsResource {
+    pub bindings: HashMap<InputAction, [Option<InputBinding>; BINDING_SLOTS]>,
+    /// Stored with the bindings, copied to `SettingsResource::controller_scheme`
+    #[serde(default)]
+    pub scheme: ControlScheme,
+    /// Stored with the bindings, copied to `SettingsResource::controller_rotate_camera`
+    #[serde(default)]
+    pub rotate_camera: bool,
+}
+
+impl Default for InputBindingsResource {
+    fn default() -> Self {
+        Self {
+            bindings: InputAction::ALL
+                .iter()
+                .map(|action| (*action, action.default_bindings()))
+                .collect(),
+            scheme: ControlScheme::default(),
+            rotate_camera: false,
+        }
+    }
+}
+
+impl InputBindingsResource {
+    pub fn get(&self, action: InputAction) -> impl Iterator<Item = &InputBinding> {
+        self.bindings
+            .get(&action)
+            .into_iter()
+            .flat_map(|slots| slots.iter().flatten())
+    }
+
+    pub fn get_slot(&self, action: InputAction, slot: usize) -> Option<InputBinding> {
+        self.bindings
+            .get(&action)
+            .and_then(|slots| slots.get(slot).copied().flatten())
+    }
+
+    /// Bind the input to the action slot, the input is removed from any other action.
+    pub fn set(&mut self, action: InputAction, slot: usize, binding: InputBinding) {
+        for slots in self.bindings.values_mut() {
+            for existing in slots.iter_mut() {
+                if *existing == Some(binding) {
+                    *existing = None;
+                }
+            }
+        }
+        let slots = self
+            .bindings
+            .entry(action)
+            .or_insert_with(|| action.default_bindings());
+        slots[slot] = Some(binding);
+    }
+
+    /// Bindings of the action joined for display, e.g. `Up / W`, after its fixed key
+    pub fn label(&self, action: InputAction) -> String {
+        let labels = action
+            .fixed_key()
+            .map(InputBinding::Key)
+            .iter()
+            .chain(self.get(action))
+            .map(|binding| binding.label())
+            .collect::<Vec<_>>();
+        if labels.is_empty() {
+            "Unbound".to_string()
+        } else {
+            labels.join(" / ")
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        let mut resource = Self::default();
+        let Ok(bytes) = std::fs::read(BINDINGS_FILE) else {
+            return resource;
+        };
+        match serde_json::from_slice::<InputBindingsResource>(&bytes) {
+            Ok(loaded) => {
+                // actions missing from the file keep their default bindings
+                resource.bindings.extend(loaded.bindings);
+                for slot in resource
+                    .bindings
+                    .values_mut()
+                    .flat_map(|slots| slots.iter_mut())
+                {
+                    if slot.is_some_and(|binding| !binding.is_bindable()) {
+                        *slot = None;
+                    }
+                }
+                resource.scheme = loaded.scheme;
+                resource.rotate_camera = loaded.rotate_camera;
+            }
+            Err(err) => {
+                warn!("Ignoring invalid controls file {}: {}", BINDINGS_FILE, err);
+            }
+        }
+        resource
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        let result = serde_json::to_vec_pretty(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| std::fs::write(BINDINGS_FILE, bytes).map_err(anyhow::Error::from));
+        if let Err(err) = result {
+            error!("Failed saving controls to {}: {}", BINDINGS_FILE, err);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {}
+}
+
+/// Input from on-screen controls (e.g. touch), merged with the bound inputs by [`ActionInput`]
+#[derive(Resource, Debug)]
+pub struct VirtualInputResource {
+    /// Analog movement, x is right and y is up, with a length of up to 1
+    pub movement: Vec2,
+    /// Aim direction in screen space (y is up), overrides the mouse cursor
+    pub aim: Option<Vec2>,
+    /// Factor the camera zoom is multiplied with this frame
+    pub zoom: f32,
+    pub pressed: HashSet<InputAction>,
+    pub just_pressed: HashSet<InputAction>,
+}
+
+impl Default for VirtualInputResource {
+    fn default() -> Self {
+        Self {
+            movement: Vec2::ZERO,
+            aim: None,
+            zoom: 1.0,
+            pressed: HashSet::default(),
+            just_pressed: HashSet::default(),
+        }
+    }
+}
+
+/// Query the state of actions using the current bindings
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    bindings: Res<'w, InputBindingsResource>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    virtual_input: Res<'w, VirtualInputResource>,
+}
+
+impl ActionInput<'_> {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.virtual_input.pressed.contains(&action)
+            || action.fixed_key().is_some_and(|key| self.keys.pressed(key))
+            || self.bindings.get(action).any(|binding| match binding {
+                InputBinding::Key(key) => self.keys.pressed(*key),
+                InputBinding::Mouse(button) => self.mouse.pressed(*button),
+            })
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.virtual_input.just_pressed.contains(&action)
+            || action
+                .fixed_key()
+                .is_some_and(|key| self.keys.just_pressed(key))
+            || self.bindings.get(action).any(|binding| match binding {
+                InputBinding::Key(key) => self.keys.just_pressed(*key),
+                InputBinding::Mouse(button) => self.mouse.just_pressed(*button),
+            })
+    }
+
+    /// Movement of all move actions and virtual input combined, x is right and y is up
+    pub fn movement(&self) -> Vec2 {
+        let mut movement = self.virtual_input.movement;
+        if self.pressed(InputAction::MoveUp) {
+            movement.y += 1.0;
+        }
+        if self.pressed(InputAction::MoveDown) {
+            movement.y -= 1.0;
+        }
+        if self.pressed(InputAction::MoveLeft) {
+            movement.x -= 1.0;
+        }
+        if self.pressed(InputAction::MoveRight) {
+            movement.x += 1.0;
+        }
+        movement.clamp_length_max(1.0)
+    }
+
+    pub fn aim(&self) -> Option<Vec2> {
+        self.virtual_input.aim
+    }
+}
+
+/// The action slot waiting for the player to press an input
+#[derive(Resource, Default, Debug)]
+pub struct RebindingResource {
+    pub target: Option<(InputAction, usize)>,
+    /// set one frame after `target`, so the click that started rebinding is ignored
+    armed: bool,
+}
+
+impl RebindingResource {
+    pub fn start(&mut self, action: InputAction, slot: usize) {
+        self.target = Some((action, slot));
+        self.armed = false;
+    }
+}
+
+/// Emitted after a new binding was stored or rebinding was cancelled
+#[derive(Event, Debug)]
+pub struct BindingChangedEvent;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBindingsResource::load());
+        app.insert_resource(RebindingResource::default());
+        app.insert_resource(VirtualInputResource::default());
+        app.add_event::<BindingChangedEvent>();
+        app.add_systems(Update, capture_rebinding_system);
+        app.add_systems(PreUpdate, sync_control_scheme_system);
+    }
+}
+
+/// Apply the stored control scheme to the settings when it is loaded or changed
+fn sync_control_scheme_system(
+    bindings: Res<InputBindingsResource>,
+    mut settings: ResMut<SettingsResource>,
+) {
+    if bindings.is_changed() {
+        settings.controller_scheme = bindings.scheme;
+        settings.controller_rotate_camera = bindings.rotate_camera;
+    }
+}
+
+fn capture_rebinding_system(
+    mut rebinding: ResMut<RebindingResource>,
+    mut bindings: ResMut<InputBindingsResource>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut events: EventWriter<BindingChangedEvent>,
+) {
+    let Some((action, slot)) = rebinding.target else {
+        return;
+    };
+    if !rebinding.armed {
+        rebinding.armed = true;
+        return;
+    }
+
+    // escape can't be bound, it cancels and keeps the previous binding
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.target = None;
+        events.send(BindingChangedEvent);
+        return;
+    }
+
+    let pressed = keys
+        .get_just_pressed()
+        .map(|key| InputBinding::Key(*key))
+        .chain(
+            mouse
+                .get_just_pressed()
+                .map(|button| InputBinding::Mouse(*button)),
+        )
+        .find(|binding| binding.is_bindable());
+
+    if let Some(binding) = pressed {
+        bindings.set(action, slot, binding);
+        bindings.save();
+        rebinding.target = None;
+        events.send(BindingChangedEvent);
+    }
+}
@@ -1,9 +1,16 @@
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore};
 use bevy::prelude::*;
 use bevy_egui::{
     egui::{self, CursorIcon},
     EguiContexts, EguiPlugin,
 };
 
+use super::controls::{ActionInput, InputAction};
+use super::icons::{ICON_INSTANCE_ALLOCATIONS, ICON_INSTANCE_UPLOAD, ICON_INSTANCE_VISIBLE};
+use super::settings::{
+    CameraLookAhead, ColorblindPalette, ControlScheme, FollowerMarker, RetroFilter,
+};
+use super::world::WorldLayoutResource;
 use super::{settings::SettingsResource, states::GameState};
 
 #[derive(Resource, Default)]
@@ -22,8 +29,8 @@ impl Plugin for DebugPlugin {
     }
 }
 
-pub fn toggle_debug(keys: Res<ButtonInput<KeyCode>>, mut show_debug: ResMut<ShowDebug>) {
-    if keys.just_pressed(KeyCode::KeyO) {
+pub fn toggle_debug(input: ActionInput, mut show_debug: ResMut<ShowDebug>) {
+    if input.just_pressed(InputAction::ToggleDebug) {
         show_debug.show = !show_debug.show;
     }
 }
@@ -33,6 +40,9 @@ pub fn render_settings_gui(
     mut contexts: EguiContexts,
     show_debug: Res<ShowDebug>,
     state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut layouts: ResMut<WorldLayoutResource>,
+    diagnostics: Res<DiagnosticsStore>,
 ) {
     if *state == GameState::GameRunning {
         contexts.ctx_mut().output_mut(|o| {
@@ -50,6 +60,43 @@ pub fn render_settings_gui(
     egui::Window::new("SettingsResource").show(contexts.ctx_mut(), |ui| {
         ui.style_mut().spacing.slider_width = 300.0;
 
+        // the world is rebuilt from the main menu
+        ui.add_enabled_ui(*state == GameState::MainMenu, |ui| {
+            ui.horizontal(|ui| {
+                let current = layouts.current;
+                egui::ComboBox::from_label("World Layout")
+                    .selected_text(layouts.current().name.clone())
+                    .show_ui(ui, |ui| {
+                        for index in 0..layouts.layouts.len() {
+                            let name = layouts.layouts[index].name.clone();
+                            ui.selectable_value(&mut layouts.current, index, name);
+                        }
+                    });
+                if layouts.current != current {
+                    next_state.set(GameState::GameLoading);
+                }
+            });
+            if ui.button("Open Level Editor").clicked() {
+                next_state.set(GameState::LevelEditor);
+            }
+        });
+
+        // averaged over the last frames
+        let average = |path: &DiagnosticPath| {
+            diagnostics
+                .get(path)
+                .and_then(|diagnostic| diagnostic.average())
+                .unwrap_or_default()
+        };
+        ui.label(format!(
+            "Icon Instances: {:.0} visible, {:.1} KiB uploaded, {:.2} buffer allocations per frame",
+            average(&ICON_INSTANCE_VISIBLE),
+            average(&ICON_INSTANCE_UPLOAD) / 1024.0,
+            average(&ICON_INSTANCE_ALLOCATIONS),
+        ));
+        ui.checkbox(&mut settings.icon_culling, "Icons: Frustum Culling");
+        ui.checkbox(&mut settings.icon_pixelated, "Icons: Pixelated");
+
         ui.add(egui::Slider::new(&mut settings.max_icons, 0..=2000).text("Max Icons (0 = all)"));
 
         ui.add(egui::Slider::new(&mut settings.max_speed, 0.0..=2.0).text("Max Speed"));
@@ -66,6 +113,14 @@ pub fn render_settings_gui(
             egui::Slider::new(&mut settings.avoidance_distance_dropzone, 0.0..=500.0)
                 .text("Avoidance Distance: Dropzone "),
         );
+        ui.add(
+            egui::Slider::new(&mut settings.avoidance_force_obstacle, 0.0..=100.0)
+                .text("Avoidance Force: Obstacle"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.avoidance_distance_obstacle, 0.0..=500.0)
+                .text("Avoidance Distance: Obstacle"),
+        );
         ui.add(
             egui::Slider::new(&mut settings.velocity_time_scale, 0.0..=4000.0)
                 .text("Velocity Time Scale"),
@@ -147,6 +202,142 @@ pub fn render_settings_gui(
             egui::Slider::new(&mut settings.controller_max_speed, 0.0..=1000.0)
                 .text("Controller Max Speed"),
         );
+        ui.horizontal(|ui| {
+            ui.label("Controller Scheme");
+            ui.radio_value(
+                &mut settings.controller_scheme,
+                ControlScheme::ScreenRelative,
+                ControlScheme::ScreenRelative.label(),
+            );
+            ui.radio_value(
+                &mut settings.controller_scheme,
+                ControlScheme::Tank,
+                ControlScheme::Tank.label(),
+            );
+        });
+        ui.checkbox(
+            &mut settings.controller_rotate_camera,
+            "Controller: Rotate Camera (Tank)",
+        );
+
+        ui.add(egui::Slider::new(&mut settings.camera_damping, 0.0..=30.0).text("Camera: Damping"));
+        ui.horizontal(|ui| {
+            ui.label("Camera Look-Ahead");
+            for look_ahead in [
+                CameraLookAhead::Off,
+                CameraLookAhead::Velocity,
+                CameraLookAhead::Aim,
+            ] {
+                ui.radio_value(
+                    &mut settings.camera_look_ahead,
+                    look_ahead,
+                    look_ahead.label(),
+                );
+            }
+        });
+        ui.add(
+            egui::Slider::new(&mut settings.camera_look_ahead_distance, 0.0..=500.0)
+                .text("Camera: Look-Ahead Distance"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_dead_zone, 0.0..=200.0)
+                .text("Camera: Dead Zone"),
+        );
+        ui.checkbox(&mut settings.camera_shake, "Camera: Screen Shake");
+        ui.add(
+            egui::Slider::new(&mut settings.camera_shake_max_offset, 0.0..=100.0)
+                .text("Camera: Shake Max Offset"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_shake_max_angle, 0.0..=0.5)
+                .text("Camera: Shake Max Angle"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_shake_decay, 0.0..=5.0)
+                .text("Camera: Shake Decay"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_shake_damage_trauma, 0.0..=1.0)
+                .text("Camera: Damage Trauma"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_shake_capture_trauma, 0.0..=1.0)
+                .text("Camera: Capture Trauma (per Icon)"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_shake_capture_threshold, 1..=50)
+                .text("Camera: Capture Trauma Threshold"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_zoom_step, 1.0..=2.0).text("Camera: Zoom Step"),
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_zoom_speed, 0.0..=30.0)
+                .text("Camera: Zoom Speed"),
+        );
+        ui.checkbox(
+            &mut settings.camera_zoom_to_cursor,
+            "Camera: Zoom to Cursor",
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.camera_fly_in_duration, 0.0..=5.0)
+                .text("Camera: Fly-In Duration"),
+        );
+
+        ui.checkbox(&mut settings.minimap, "Minimap");
+        ui.add(egui::Slider::new(&mut settings.minimap_size, 64.0..=512.0).text("Minimap: Size"));
+        ui.add(
+            egui::Slider::new(&mut settings.minimap_opacity, 0.0..=1.0).text("Minimap: Opacity"),
+        );
+
+        ui.checkbox(&mut settings.bloom, "Bloom");
+        ui.add(
+            egui::Slider::new(&mut settings.bloom_intensity, 0.0..=1.0).text("Bloom: Intensity"),
+        );
+        ui.checkbox(
+            &mut settings.chromatic_aberration,
+            "Chromatic Aberration on Damage",
+        );
+        ui.add(
+            egui::Slider::new(&mut settings.chromatic_aberration_strength, 0.0..=64.0)
+                .text("Chromatic Aberration: Strength"),
+        );
+        ui.horizontal(|ui| {
+            ui.label("Retro Filter");
+            for filter in [RetroFilter::Off, RetroFilter::Scanlines, RetroFilter::Crt] {
+                ui.radio_value(&mut settings.retro_filter, filter, filter.label());
+            }
+        });
+
+        ui.checkbox(&mut settings.particles, "Particles");
+        ui.add(
+            egui::Slider::new(&mut settings.particles_amount, 0.0..=4.0).text("Particles: Amount"),
+        );
+        ui.add(egui::Slider::new(&mut settings.particles_max, 0..=65536).text("Particles: Max"));
+
+        ui.horizontal(|ui| {
+            ui.label("Colorblind Palette");
+            for palette in [
+                ColorblindPalette::Off,
+                ColorblindPalette::RedGreen,
+                ColorblindPalette::BlueYellow,
+            ] {
+                ui.radio_value(&mut settings.colorblind_palette, palette, palette.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Follower Marker");
+            for marker in [
+                FollowerMarker::Ring,
+                FollowerMarker::DashedRing,
+                FollowerMarker::Badge,
+            ] {
+                ui.radio_value(&mut settings.follower_marker, marker, marker.label());
+            }
+        });
+        ui.checkbox(&mut settings.reduced_motion, "Reduced Motion");
+        ui.add(egui::Slider::new(&mut settings.ui_scale, 0.5..=2.0).text("UI Scale"));
+        ui.add(egui::Slider::new(&mut settings.game_speed, 0.25..=1.0).text("Game Speed"));
 
         ui.add(
             egui::Slider::new(&mut settings.max_hover_distance, 0.0..=1000.0)
@@ -0,0 +1,725 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+
+use super::assets::icons::AssetError;
+use super::assets::levels::LevelFile;
+use super::camera::{CameraTag, CAMERA_LAYER, CAMERA_LAYER_EDITOR};
+use super::hud::ScreenTag;
+use super::icons::ICON_CIRCLE_RADIUS;
+use super::settings::SettingsResource;
+use super::states::GameState;
+use super::theme::{ThemeColor, ThemeResource};
+use super::world::{
+    DropzoneLayout, Obstacle, SpawnRegion, WinCondition, WorldLayout, WorldLayoutResource,
+};
+
+const COLOR_GRID: ThemeColor = ThemeColor::Grid;
+const COLOR_BOUNDS: ThemeColor = ThemeColor::ObstacleBorder;
+const COLOR_DROPZONE: ThemeColor = ThemeColor::Accent;
+const COLOR_OBSTACLE: ThemeColor = ThemeColor::Text;
+const COLOR_SPAWN_REGION: ThemeColor = ThemeColor::Projectile;
+const COLOR_PLAYER: ThemeColor = ThemeColor::Damage;
+const COLOR_SELECTED: ThemeColor = ThemeColor::Selection;
+
+/// Pick distance in pixels for items without an area
+const PICK_DISTANCE: f32 = 12.0;
+
+/// Part of the level that can be selected and moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditorItem {
+    PlayerStart,
+    Dropzone(usize),
+    Obstacle(usize),
+    SpawnRegion(usize),
+}
+
+/// What a left click on the world does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum EditorTool {
+    #[default]
+    Select,
+    Dropzone,
+    Rectangle,
+    Circle,
+    Polygon,
+    SpawnRegion,
+}
+
+impl EditorTool {
+    const ALL: [EditorTool; 6] = [
+        EditorTool::Select,
+        EditorTool::Dropzone,
+        EditorTool::Rectangle,
+        EditorTool::Circle,
+        EditorTool::Polygon,
+        EditorTool::SpawnRegion,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            EditorTool::Select => "Select",
+            EditorTool::Dropzone => "Dropzone",
+            EditorTool::Rectangle => "Rectangle",
+            EditorTool::Circle => "Circle",
+            EditorTool::Polygon => "Polygon",
+            EditorTool::SpawnRegion => "Spawn Region",
+        }
+    }
+}
+
+/// Level being edited, it replaces the current level of the [`WorldLayoutResource`]
+/// for play tests and when the editor is closed
+#[derive(Resource)]
+struct LevelEditorResource {
+    layout: WorldLayout,
+    tool: EditorTool,
+    selection: Option<EditorItem>,
+    /// Offset from the cursor to the dragged item
+    drag: Option<Vec2>,
+    /// Last cursor position while panning the camera
+    pan: Option<Vec2>,
+    /// File the level is saved to
+    path: String,
+    status: String,
+    /// Set while a play test runs, returning to the editor keeps the edits
+    play_testing: bool,
+    /// Start the game as soon as the world is rebuilt
+    start_play_test: bool,
+}
+
+impl Default for LevelEditorResource {
+    fn default() -> Self {
+        Self {
+            layout: WorldLayout::classic(),
+            tool: EditorTool::default(),
+            selection: None,
+            drag: None,
+            pan: None,
+            path: String::new(),
+            status: String::new(),
+            play_testing: false,
+            start_play_test: false,
+        }
+    }
+}
+
+pub struct LevelEditorPlugin;
+
+impl Plugin for LevelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelEditorResource::default());
+        app.add_systems(Startup, setup_editor_gizmos);
+        app.add_systems(OnEnter(GameState::LevelEditor), enter_editor_system);
+        app.add_systems(OnExit(GameState::LevelEditor), exit_editor_system);
+        app.add_systems(OnEnter(GameState::MainMenu), start_play_test_system);
+        app.add_systems(
+            Update,
+            (editor_ui_system, editor_mouse_system, draw_editor_system)
+                .chain()
+                .run_if(in_state(GameState::LevelEditor)),
+        );
+        app.add_systems(
+            Update,
+            play_test_ui_system.run_if(
+                in_state(GameState::GameRunning)
+                    .or_else(in_state(GameState::GamePaused))
+                    .or_else(in_state(GameState::GameOver)),
+            ),
+        );
+    }
+}
+
+fn setup_editor_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.render_layers = RenderLayers::layer(CAMERA_LAYER_EDITOR);
+}
+
+fn level_path(layout: &WorldLayout) -> String {
+    format!(
+        "assets/levels/{}.level.json",
+        layout.name.to_lowercase().replace(' ', "-")
+    )
+}
+
+/// Checks the level the same way as loading it from a file does
+fn validate_level(layout: &WorldLayout) -> Result<(), AssetError> {
+    WorldLayout::try_from(LevelFile::from(layout)).map(|_| ())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_level(path: &str, layout: &WorldLayout) -> anyhow::Result<()> {
+    validate_level(layout)?;
+    let bytes = serde_json::to_vec_pretty(&LevelFile::from(layout))?;
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_level(_path: &str, _layout: &WorldLayout) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("Saving levels is not supported on the web"))
+}
+
+fn item_position(layout: &WorldLayout, item: EditorItem) -> Option<Vec2> {
+    match item {
+        EditorItem::PlayerStart => Some(layout.player_start),
+        EditorItem::Dropzone(index) => layout.dropzones.get(index).map(|dropzone| dropzone.center),
+        EditorItem::Obstacle(index) => layout.obstacles.get(index).map(|obstacle| {
+            let (min, max) = obstacle.bounds();
+            (min + max) / 2.0
+        }),
+        EditorItem::SpawnRegion(index) => {
+            layout.spawn_regions.get(index).map(|region| region.center)
+        }
+    }
+}
+
+fn move_item(layout: &mut WorldLayout, item: EditorItem, position: Vec2) {
+    let Some(offset) = item_position(layout, item).map(|current| position - current) else {
+        return;
+    };
+    match item {
+        EditorItem::PlayerStart => layout.player_start = position,
+        EditorItem::Dropzone(index) => layout.dropzones[index].center = position,
+        EditorItem::Obstacle(index) => match &mut layout.obstacles[index] {
+            Obstacle::Rectangle { center, .. } | Obstacle::Circle { center, .. } => {
+                *center = position
+            }
+            Obstacle::Polygon { points } => {
+                for point in points.iter_mut() {
+                    *point += offset;
+                }
+            }
+        },
+        EditorItem::SpawnRegion(index) => layout.spawn_regions[index].center = position,
+    }
+}
+
+fn remove_item(layout: &mut WorldLayout, item: EditorItem) {
+    match item {
+        // the level always needs a player start
+        EditorItem::PlayerStart => {}
+        EditorItem::Dropzone(index) => {
+            layout.dropzones.remove(index);
+        }
+        EditorItem::Obstacle(index) => {
+            layout.obstacles.remove(index);
+        }
+        EditorItem::SpawnRegion(index) => {
+            layout.spawn_regions.remove(index);
+        }
+    }
+}
+
+/// Topmost item under the point, in the order they are drawn
+fn item_at(layout: &WorldLayout, point: Vec2, pick_distance: f32) -> Option<EditorItem> {
+    let in_rect = |center: Vec2, size: Vec2| {
+        let offset = (point - center).abs();
+        offset.x <= size.x / 2.0 && offset.y <= size.y / 2.0
+    };
+
+    if layout.player_start.distance(point) <= pick_distance {
+        return Some(EditorItem::PlayerStart);
+    }
+    if let Some(index) = layout
+        .obstacles
+        .iter()
+        .rposition(|obstacle| obstacle.contains(point))
+    {
+        return Some(EditorItem::Obstacle(index));
+    }
+    if let Some(index) = layout
+        .spawn_regions
+        .iter()
+        .rposition(|region| in_rect(region.center, region.size))
+    {
+        return Some(EditorItem::SpawnRegion(index));
+    }
+    layout
+        .dropzones
+        .iter()
+        .rposition(|dropzone| in_rect(dropzone.center, dropzone.size))
+        .map(EditorItem::Dropzone)
+}
+
+/// Adds a new item with a default size centered on the position
+fn add_item(layout: &mut WorldLayout, tool: EditorTool, position: Vec2) -> Option<EditorItem> {
+    match tool {
+        EditorTool::Select => None,
+        EditorTool::Dropzone => {
+            layout
+                .dropzones
+                .push(DropzoneLayout::new(position, Vec2::splat(1024.0)));
+            Some(EditorItem::Dropzone(layout.dropzones.len() - 1))
+        }
+        EditorTool::Rectangle => {
+            layout.obstacles.push(Obstacle::Rectangle {
+                center: position,
+                size: Vec2::splat(512.0),
+            });
+            Some(EditorItem::Obstacle(layout.obstacles.len() - 1))
+        }
+        EditorTool::Circle => {
+            layout.obstacles.push(Obstacle::Circle {
+                center: position,
+                radius: 256.0,
+            });
+            Some(EditorItem::Obstacle(layout.obstacles.len() - 1))
+        }
+        EditorTool::Polygon => {
+            let points = (0..6)
+                .map(|i| {
+                    let angle = i as f32 / 6.0 * std::f32::consts::TAU;
+                    position + Vec2::from_angle(angle) * 256.0
+                })
+                .collect();
+            layout.obstacles.push(Obstacle::Polygon { points });
+            Some(EditorItem::Obstacle(layout.obstacles.len() - 1))
+        }
+        EditorTool::SpawnRegion => {
+            layout.spawn_regions.push(SpawnRegion {
+                center: position,
+                size: Vec2::splat(2048.0),
+            });
+            Some(EditorItem::SpawnRegion(layout.spawn_regions.len() - 1))
+        }
+    }
+}
+
+fn enter_editor_system(
+    mut commands: Commands,
+    mut editor: ResMut<LevelEditorResource>,
+    layouts: Res<WorldLayoutResource>,
+    screens: Query<Entity, With<ScreenTag>>,
+    mut camera: Query<(&mut RenderLayers, &mut Transform), With<CameraTag>>,
+) {
+    for screen_entity in screens.iter() {
+        commands.entity(screen_entity).despawn_recursive();
+    }
+    for (mut render_layers, mut transform) in camera.iter_mut() {
+        *render_layers = RenderLayers::layer(CAMERA_LAYER_EDITOR);
+        transform.rotation = Quat::IDENTITY;
+    }
+
+    // coming back from a play test keeps the edits
+    if !editor.play_testing {
+        editor.layout = layouts.current().clone();
+        editor.path = level_path(&editor.layout);
+        editor.selection = None;
+        editor.status.clear();
+    }
+    editor.play_testing = false;
+    editor.drag = None;
+    editor.pan = None;
+}
+
+fn exit_editor_system(mut camera: Query<&mut RenderLayers, With<CameraTag>>) {
+    for mut render_layers in camera.iter_mut() {
+        *render_layers = RenderLayers::layer(CAMERA_LAYER);
+    }
+}
+
+fn start_play_test_system(
+    mut editor: ResMut<LevelEditorResource>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if editor.start_play_test {
+        editor.start_play_test = false;
+        state.set(GameState::GameRunning);
+    } else {
+        // back in the main menu, the play test is over
+        editor.play_testing = false;
+    }
+}
+
+fn vec2_ui(ui: &mut egui::Ui, label: &str, value: &mut Vec2, speed: f32) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add(
+            egui::DragValue::new(&mut value.x)
+                .speed(speed)
+                .prefix("x: "),
+        );
+        ui.add(
+            egui::DragValue::new(&mut value.y)
+                .speed(speed)
+                .prefix("y: "),
+        );
+    });
+}
+
+fn selection_ui(ui: &mut egui::Ui, layout: &mut WorldLayout, item: EditorItem) {
+    match item {
+        EditorItem::PlayerStart => {
+            ui.label("Player Start");
+            vec2_ui(ui, "Position", &mut layout.player_start, 8.0);
+        }
+        EditorItem::Dropzone(index) => {
+            let dropzone = &mut layout.dropzones[index];
+            ui.label(format!("Dropzone #{}", index));
+            vec2_ui(ui, "Center", &mut dropzone.center, 8.0);
+            vec2_ui(ui, "Size", &mut dropzone.size, 8.0);
+            vec2_ui(ui, "Velocity", &mut dropzone.velocity, 1.0);
+            ui.add(
+                egui::DragValue::new(&mut dropzone.shrink_rate)
+                    .speed(1.0)
+                    .prefix("Shrink Rate: "),
+            );
+            vec2_ui(ui, "Min Size", &mut dropzone.min_size, 8.0);
+        }
+        EditorItem::Obstacle(index) => match &mut layout.obstacles[index] {
+            Obstacle::Rectangle { center, size } => {
+                ui.label(format!("Rectangle #{}", index));
+                vec2_ui(ui, "Center", center, 8.0);
+                vec2_ui(ui, "Size", size, 8.0);
+            }
+            Obstacle::Circle { center, radius } => {
+                ui.label(format!("Circle #{}", index));
+                vec2_ui(ui, "Center", center, 8.0);
+                ui.add(
+                    egui::DragValue::new(radius)
+                        .speed(8.0)
+                        .clamp_range(1.0..=f32::MAX)
+                        .prefix("Radius: "),
+                );
+            }
+            Obstacle::Polygon { points } => {
+                ui.label(format!("Polygon #{}", index));
+                let mut removed = None;
+                for (i, point) in points.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        vec2_ui(ui, &format!("Point {}", i), point, 8.0);
+                        if ui.small_button("x").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+                // polygons need at least three points
+                if let Some(i) = removed.filter(|_| points.len() > 3) {
+                    points.remove(i);
+                }
+                if ui.button("Add Point").clicked() {
+                    let (first, last) = (points[0], points[points.len() - 1]);
+                    points.push((first + last) / 2.0);
+                }
+            }
+        },
+        EditorItem::SpawnRegion(index) => {
+            let region = &mut layout.spawn_regions[index];
+            ui.label(format!("Spawn Region #{}", index));
+            vec2_ui(ui, "Center", &mut region.center, 8.0);
+            vec2_ui(ui, "Size", &mut region.size, 8.0);
+        }
+    }
+}
+
+fn level_ui(ui: &mut egui::Ui, layout: &mut WorldLayout) {
+    ui.horizontal(|ui| {
+        ui.label("Name");
+        ui.text_edit_singleline(&mut layout.name);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Description");
+        ui.text_edit_singleline(&mut layout.description);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Player Icon");
+        ui.text_edit_singleline(&mut layout.player_icon);
+    });
+    vec2_ui(ui, "World Size", &mut layout.size, 16.0);
+    ui.add(
+        egui::DragValue::new(&mut layout.grid_spacing)
+            .speed(8.0)
+            .clamp_range(16.0..=4096.0)
+            .prefix("Grid Spacing: "),
+    );
+    ui.checkbox(&mut layout.wrap, "Wrap Around Edges");
+
+    ui.horizontal(|ui| {
+        ui.label("Win Condition");
+        let win_condition = &mut layout.win_condition;
+        if ui
+            .selectable_label(
+                matches!(win_condition, WinCondition::CaptureAll),
+                "Capture All",
+            )
+            .clicked()
+        {
+            *win_condition = WinCondition::CaptureAll;
+        }
+        if ui
+            .selectable_label(matches!(win_condition, WinCondition::Score { .. }), "Score")
+            .clicked()
+        {
+            *win_condition = WinCondition::Score { score: 1000 };
+        }
+        if ui
+            .selectable_label(
+                matches!(win_condition, WinCondition::Capture { .. }),
+                "Capture",
+            )
+            .clicked()
+        {
+            *win_condition = WinCondition::Capture { count: 100 };
+        }
+        match win_condition {
+            WinCondition::CaptureAll => {}
+            WinCondition::Score { score } => {
+                ui.add(egui::DragValue::new(score).speed(10.0));
+            }
+            WinCondition::Capture { count } => {
+                ui.add(egui::DragValue::new(count));
+            }
+        }
+    });
+}
+
+fn editor_ui_system(
+    mut contexts: EguiContexts,
+    mut editor: ResMut<LevelEditorResource>,
+    mut layouts: ResMut<WorldLayoutResource>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let editor = editor.as_mut();
+    egui::Window::new("Level Editor").show(contexts.ctx_mut(), |ui| {
+        level_ui(ui, &mut editor.layout);
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            for tool in EditorTool::ALL {
+                ui.radio_value(&mut editor.tool, tool, tool.label());
+            }
+        });
+        ui.label("Left click to select, place and drag, right drag to pan, scroll to zoom.");
+
+        ui.separator();
+        match editor.selection {
+            Some(item) if item_position(&editor.layout, item).is_some() => {
+                selection_ui(ui, &mut editor.layout, item);
+                let can_delete = item != EditorItem::PlayerStart;
+                if ui
+                    .add_enabled(can_delete, egui::Button::new("Delete"))
+                    .clicked()
+                {
+                    remove_item(&mut editor.layout, item);
+                    editor.selection = None;
+                }
+            }
+            _ => {
+                ui.label("Nothing selected");
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.text_edit_singleline(&mut editor.path);
+            if ui.button("Save").clicked() {
+                editor.status = match save_level(&editor.path, &editor.layout) {
+                    Ok(()) => format!("Saved to {}", editor.path),
+                    Err(err) => format!("Failed saving level: {}", err),
+                };
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let play_test = ui.button("Play Test").clicked();
+            let close = ui.button("Close").clicked();
+            if play_test || close {
+                match validate_level(&editor.layout) {
+                    Ok(()) => {
+                        let current = layouts.current.min(layouts.layouts.len() - 1);
+                        layouts.layouts[current] = editor.layout.clone();
+                        layouts.current = current;
+                        editor.play_testing = play_test;
+                        editor.start_play_test = play_test;
+                        next_state.set(GameState::GameLoading);
+                    }
+                    Err(err) => editor.status = err.to_string(),
+                }
+            }
+        });
+
+        if !editor.status.is_empty() {
+            ui.label(&editor.status);
+        }
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor_mouse_system(
+    mut contexts: EguiContexts,
+    mut editor: ResMut<LevelEditorResource>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<CameraTag>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut scroll_events: EventReader<MouseWheel>,
+    settings: Res<SettingsResource>,
+) {
+    let (Ok(window), Ok((mut transform, mut projection))) =
+        (window.get_single(), camera.get_single_mut())
+    else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    let over_ui = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+    let editor = editor.as_mut();
+
+    if keys.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) && !ctx.wants_keyboard_input() {
+        if let Some(item) = editor.selection.take() {
+            remove_item(&mut editor.layout, item);
+        }
+    }
+
+    let Some(cursor) = window.cursor_position() else {
+        scroll_events.clear();
+        return;
+    };
+    let offset = Vec2::new(
+        cursor.x - window.width() / 2.0,
+        window.height() / 2.0 - cursor.y,
+    );
+    let to_world = |translation: Vec3, scale: f32| translation.truncate() + offset * scale;
+
+    // zoom, keeping the world position under the cursor in place
+    for event in scroll_events.read() {
+        if over_ui || event.y == 0.0 {
+            continue;
+        }
+        let anchor = to_world(transform.translation, projection.scale);
+        projection.scale = (projection.scale * settings.camera_zoom_step.powf(-event.y.signum()))
+            .clamp(0.25, 100.0);
+        let translation = anchor - offset * projection.scale;
+        transform.translation = translation.extend(transform.translation.z);
+    }
+
+    // pan with the right or middle mouse button
+    if mouse.any_pressed([MouseButton::Right, MouseButton::Middle]) {
+        if let Some(last) = editor.pan {
+            let delta = (cursor - last) * projection.scale;
+            transform.translation.x -= delta.x;
+            transform.translation.y += delta.y;
+        }
+        editor.pan = Some(cursor);
+    } else {
+        editor.pan = None;
+    }
+
+    let position = to_world(transform.translation, projection.scale);
+    if mouse.just_pressed(MouseButton::Left) && !over_ui {
+        if editor.tool == EditorTool::Select {
+            let pick_distance = ICON_CIRCLE_RADIUS.max(PICK_DISTANCE * projection.scale);
+            editor.selection = item_at(&editor.layout, position, pick_distance);
+        } else {
+            editor.selection = add_item(&mut editor.layout, editor.tool, position);
+            editor.tool = EditorTool::Select;
+        }
+        editor.drag = editor
+            .selection
+            .and_then(|item| item_position(&editor.layout, item))
+            .map(|item_position| item_position - position);
+    }
+    if !mouse.pressed(MouseButton::Left) {
+        editor.drag = None;
+    }
+    if let (Some(item), Some(drag)) = (editor.selection, editor.drag) {
+        move_item(&mut editor.layout, item, position + drag);
+    }
+}
+
+fn draw_obstacle(gizmos: &mut Gizmos, obstacle: &Obstacle, color: Color) {
+    match obstacle {
+        Obstacle::Rectangle { center, size } => {
+            gizmos.rect_2d(*center, 0.0, *size, color);
+        }
+        Obstacle::Circle { center, radius } => {
+            gizmos.circle_2d(*center, *radius, color);
+        }
+        Obstacle::Polygon { points } => {
+            gizmos.linestrip_2d(points.iter().chain(points.first()).copied(), color);
+        }
+    }
+}
+
+fn draw_editor_system(
+    mut gizmos: Gizmos,
+    editor: Res<LevelEditorResource>,
+    theme: Res<ThemeResource>,
+) {
+    let layout = &editor.layout;
+    let color = |item: EditorItem, color: ThemeColor| {
+        if editor.selection == Some(item) {
+            theme.color(COLOR_SELECTED)
+        } else {
+            theme.color(color)
+        }
+    };
+
+    let (min, max) = (-layout.size / 2.0, layout.size / 2.0);
+    let grid_color = theme.color(COLOR_GRID);
+    let mut x = min.x + layout.grid_spacing;
+    while x < max.x {
+        gizmos.line_2d(Vec2::new(x, min.y), Vec2::new(x, max.y), grid_color);
+        x += layout.grid_spacing;
+    }
+    let mut y = min.y + layout.grid_spacing;
+    while y < max.y {
+        gizmos.line_2d(Vec2::new(min.x, y), Vec2::new(max.x, y), grid_color);
+        y += layout.grid_spacing;
+    }
+    gizmos.rect_2d(Vec2::ZERO, 0.0, layout.size, theme.color(COLOR_BOUNDS));
+
+    for (index, dropzone) in layout.dropzones.iter().enumerate() {
+        let color = color(EditorItem::Dropzone(index), COLOR_DROPZONE);
+        gizmos.rect_2d(dropzone.center, 0.0, dropzone.size, color);
+        if dropzone.shrink_rate > 0.0 {
+            gizmos.rect_2d(dropzone.center, 0.0, dropzone.min_size, color.with_a(0.5));
+        }
+        if dropzone.velocity != Vec2::ZERO {
+            gizmos.arrow_2d(dropzone.center, dropzone.center + dropzone.velocity, color);
+        }
+    }
+
+    for (index, region) in layout.spawn_regions.iter().enumerate() {
+        let color = color(EditorItem::SpawnRegion(index), COLOR_SPAWN_REGION);
+        gizmos.rect_2d(region.center, 0.0, region.size, color.with_a(0.6));
+    }
+
+    for (index, obstacle) in layout.obstacles.iter().enumerate() {
+        let color = color(EditorItem::Obstacle(index), COLOR_OBSTACLE);
+        draw_obstacle(&mut gizmos, obstacle, color);
+    }
+
+    let color = color(EditorItem::PlayerStart, COLOR_PLAYER);
+    gizmos.circle_2d(layout.player_start, ICON_CIRCLE_RADIUS, color);
+    gizmos.line_2d(
+        layout.player_start - Vec2::X * ICON_CIRCLE_RADIUS,
+        layout.player_start + Vec2::X * ICON_CIRCLE_RADIUS,
+        color,
+    );
+    gizmos.line_2d(
+        layout.player_start - Vec2::Y * ICON_CIRCLE_RADIUS,
+        layout.player_start + Vec2::Y * ICON_CIRCLE_RADIUS,
+        color,
+    );
+}
+
+/// Small window to end a play test, only shown while one runs
+fn play_test_ui_system(
+    mut contexts: EguiContexts,
+    editor: Res<LevelEditorResource>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !editor.play_testing {
+        return;
+    }
+    egui::Window::new("Play Test").show(contexts.ctx_mut(), |ui| {
+        ui.label(&editor.layout.name);
+        if ui.button("Back to Editor").clicked() {
+            next_state.set(GameState::LevelEditor);
+        }
+    });
+}
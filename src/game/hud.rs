@@ -3,11 +3,19 @@ use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy::{ecs::system::Command, render::view::RenderLayers};
 
-use super::assets::PendingAssets;
+use super::assets::{IconPacksResource, PendingAssets};
 use super::audio::AudioSettingsResource;
+use super::controls::{
+    ActionInput, BindingChangedEvent, InputAction, InputBinding, InputBindingsResource,
+    RebindingResource, BINDING_SLOTS,
+};
 use super::icons::events::PlayerFollowEvent;
 use super::icons::health::{PlayerHealth, PlayerScore};
 use super::icons::{IconSheetRef, IconType, Type};
+use super::locale::{Locale, LocaleResource, TextKey};
+use super::settings::{ColorblindPalette, ControlScheme, FollowerMarker, RetroFilter};
+use super::theme::{Theme, ThemeColor, ThemeResource, ThemedBackground, ThemedText};
+use super::world::WorldLayoutResource;
 use super::{camera::CAMERA_LAYER_UI, settings::SettingsResource, states::GameState};
 
 #[derive(Component)]
@@ -22,6 +30,7 @@ pub struct LoadingScreen {
 
 impl Command for LoadingScreen {
     fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
         if let Ok(mut style) = world
             .query_filtered::<&mut Style, With<LoadingBarTag>>()
             .get_single_mut(world)
@@ -55,9 +64,10 @@ impl Command for LoadingScreen {
                                     padding: UiRect::all(Val::Px(6.0)),
                                     ..Default::default()
                                 },
-                                background_color: Color::hex("#3c454f").unwrap().into(),
+                                background_color: theme.color(ThemeColor::Bar).into(),
                                 ..Default::default()
                             },
+                            ThemedBackground(ThemeColor::Bar),
                             RenderLayers::layer(CAMERA_LAYER_UI),
                         ))
                         .with_children(|parent| {
@@ -68,9 +78,10 @@ impl Command for LoadingScreen {
                                         height: Val::Percent(100.0),
                                         ..Default::default()
                                     },
-                                    background_color: Color::hex("#56837f").unwrap().into(),
+                                    background_color: theme.color(ThemeColor::BarFill).into(),
                                     ..Default::default()
                                 },
+                                ThemedBackground(ThemeColor::BarFill),
                                 RenderLayers::layer(CAMERA_LAYER_UI),
                                 LoadingBarTag,
                             ));
@@ -80,7 +91,7 @@ impl Command for LoadingScreen {
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Debug, Clone, Default)]
 pub struct FontResource {
     pub title: Handle<Font>,
     pub text: Handle<Font>,
@@ -96,6 +107,7 @@ pub struct FollowScreen {
 
 impl Command for FollowScreen {
     fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
         world.resource_scope::<FontResource, ()>(|world, resource| {
             world
                 .spawn((
@@ -126,9 +138,10 @@ impl Command for FollowScreen {
                                     align_items: AlignItems::Center,
                                     ..Default::default()
                                 },
-                                background_color: Color::hex("#22272eDD").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                                background_color: theme.color(ThemeColor::HudPanel).into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                 ..Default::default()
                             },
+                            ThemedBackground(ThemeColor::HudPanel),
                             RenderLayers::layer(CAMERA_LAYER_UI),
                         ))
                         .with_children(|child| {
@@ -138,9 +151,10 @@ impl Command for FollowScreen {
                                     TextStyle {
                                         font: resource.title.clone(),
                                         font_size: 48.0,
-                                        color: Color::hex("#6b9894").unwrap(),
+                                        color: theme.color(ThemeColor::Accent),
                                     },
                                 ),
+                                ThemedText(ThemeColor::Accent),
                                 RenderLayers::layer(CAMERA_LAYER_UI),
                             ));
                         });
@@ -179,12 +193,16 @@ impl ScoreScreen {
 
 impl Command for ScoreScreen {
     fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
         world.resource_scope::<FontResource, ()>(|world, resource| {
             world.resource_scope::<DiagnosticsStore, ()>(|world, diagnostics| {
-                let mut contents = format!("Score: {}", self.score_line()); // \nFPS: {:.2}", self.score_line(), diagn);
+                let mut contents = locale.format(TextKey::Score, &[&self.score_line()]);
                 if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
                     if let Some(value) = fps.smoothed() {
-                        contents.push_str(&format!("\n{:.2} FPS", value));
+                        contents.push('\n');
+                        contents
+                            .push_str(&locale.format(TextKey::Fps, &[&format!("{:.2}", value)]));
                     }
                 }
 
@@ -193,6 +211,10 @@ impl Command for ScoreScreen {
                     .get_single_mut(world)
                 {
                     text.sections[0].value = contents;
+                    // the language might have switched to one with other fonts
+                    if text.sections[0].style.font != resource.text {
+                        text.sections[0].style.font = resource.text.clone();
+                    }
                     if let Ok(mut style) = world
                         .query_filtered::<&mut Style, With<HealthBarTag>>()
                         .get_single_mut(world)
@@ -232,9 +254,10 @@ impl Command for ScoreScreen {
                                             left: Val::Px(32.0),
                                             ..Default::default()
                                         },
-                                        background_color: Color::hex("#3c454f").unwrap().into(),
+                                        background_color: theme.color(ThemeColor::Bar).into(),
                                         ..Default::default()
                                     },
+                                    ThemedBackground(ThemeColor::Bar),
                                     RenderLayers::layer(CAMERA_LAYER_UI),
                                 ))
                                 .with_children(|parent| {
@@ -245,9 +268,12 @@ impl Command for ScoreScreen {
                                                 height: Val::Percent(100.0),
                                                 ..Default::default()
                                             },
-                                            background_color: Color::hex("#56837f").unwrap().into(),
+                                            background_color: theme
+                                                .color(ThemeColor::BarFill)
+                                                .into(),
                                             ..Default::default()
                                         },
+                                        ThemedBackground(ThemeColor::BarFill),
                                         RenderLayers::layer(CAMERA_LAYER_UI),
                                         HealthBarTag,
                                     ));
@@ -265,9 +291,10 @@ impl Command for ScoreScreen {
                                             align_items: AlignItems::Center,
                                             ..Default::default()
                                         },
-                                        background_color: Color::hex("#22272eDD").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                                        background_color: theme.color(ThemeColor::HudPanel).into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                         ..Default::default()
                                     },
+                                    ThemedBackground(ThemeColor::HudPanel),
                                     RenderLayers::layer(CAMERA_LAYER_UI),
                                 ))
                                 .with_children(|parent| {
@@ -277,9 +304,10 @@ impl Command for ScoreScreen {
                                             TextStyle {
                                                 font: resource.text.clone(),
                                                 font_size: 32.0,
-                                                color: Color::hex("#6b9894").unwrap(),
+                                                color: theme.color(ThemeColor::Accent),
                                             },
                                         ),
+                                        ThemedText(ThemeColor::Accent),
                                         RenderLayers::layer(CAMERA_LAYER_UI),
                                         ScoreScreenTextTag,
                                     ));
@@ -298,22 +326,67 @@ enum ButtonKind {
     ResumeGame,
     ToggleMusic,
     ToggleSound,
+    ToggleScreenShake,
     BackToMainMenu,
     QuitGame,
+    OpenControls,
+    CloseControls,
+    ResetControls,
+    ToggleControlScheme,
+    ToggleCameraRotation,
+    /// Rebind the action slot (primary / secondary)
+    Rebind(InputAction, usize),
+    OpenLevelSelect,
+    CloseLevelSelect,
+    /// Switch to the level with this index
+    SelectLevel(usize),
+    OpenIconPackSelect,
+    CloseIconPackSelect,
+    /// Switch to the icon pack with this index
+    SelectIconPack(usize),
+    OpenGraphics,
+    CloseGraphics,
+    ToggleBloom,
+    ToggleChromaticAberration,
+    CycleRetroFilter,
+    TogglePixelated,
+    CycleTheme,
+    OpenAccessibility,
+    CloseAccessibility,
+    CycleColorblindPalette,
+    CycleFollowerMarker,
+    ToggleReducedMotion,
+    CycleUiScale,
+    CycleGameSpeed,
+    CycleLanguage,
 }
 
 pub struct ButtonChildBuilder {
-    label: &'static str,
+    label: String,
     kind: ButtonKind,
+    width: f32,
 }
 
 impl ButtonChildBuilder {
-    fn spawn(&self, parent: &mut WorldChildBuilder, resource: &FontResource) {
+    fn new(label: impl Into<String>, kind: ButtonKind) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+            width: 350.0,
+        }
+    }
+
+    fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    fn spawn(&self, parent: &mut WorldChildBuilder, resource: &FontResource, theme: &Theme) {
         parent
             .spawn((
                 ButtonBundle {
                     style: Style {
-                        width: Val::Px(350.0),
+                        width: Val::Px(self.width),
                         height: Val::Px(65.0),
                         border: UiRect::all(Val::Px(0.0)),
                         padding: UiRect::all(Val::Px(16.0)),
@@ -324,10 +397,11 @@ impl ButtonChildBuilder {
                         ..default()
                     },
                     // border_color: BorderColor(Color::hex("#444c56").unwrap()),
-                    background_color: Color::hex("#2d333b").unwrap().into(), // hex("#6b9894DD").unwrap().into(),
+                    background_color: theme.color(ThemeColor::Button).into(), // hex("#6b9894DD").unwrap().into(),
                     ..default()
                 },
                 self.kind,
+                ThemedBackground(ThemeColor::Button),
                 RenderLayers::layer(CAMERA_LAYER_UI),
             ))
             .with_children(|parent| {
@@ -337,9 +411,10 @@ impl ButtonChildBuilder {
                         TextStyle {
                             font: resource.text.clone(),
                             font_size: 32.0,
-                            color: Color::hex("#7fc1bb").unwrap(),
+                            color: theme.color(ThemeColor::ButtonText),
                         },
                     ),
+                    ThemedText(ThemeColor::ButtonText),
                     RenderLayers::layer(CAMERA_LAYER_UI),
                 ));
             });
@@ -351,35 +426,72 @@ pub struct TitleScreen {
 }
 
 impl TitleScreen {
-    const CREDITS: &'static str = "Game by @mattzq for Bevy Jam #4
-Font Awesome Icons by Fonticons, Inc. (CC-BY-4.0)
-Music by Brylie Christopher Oxley (CC-BY-4.0)
-SFX by rubberduck (CC0)
-Gasoek One by Jiashuo Zhang and JAMO (OFL)
-DM Sans by Colophon Foundry, Jonny Pinhorn and Indian Type Foundry (OFL)
-
-and Bevy (https://bevyengine.org/)";
-    const INSTRUCTIONS: &'static str = "Instructions:
-Shoot icons to make them follow you.
-You take damage if they touch you.
-Bring them to the dropzone (the center area) to score points.
-You make more points the more followers you bring at once.
-You take more damage the more followers you have.
-    
-Controls:
-Up / W - Move Forward 
-Down / S - Move Backward 
-Left / A - Strafe Left
-Right / D - Strafe Right
-Space / Left Click - Shoot
-Mouse Wheel - Zoom
-Escape - Pause Menu";
+    /// Credits with the ones of the current icon pack
+    pub fn credits(packs: &IconPacksResource, locale: &Locale) -> String {
+        let icons = packs
+            .current()
+            .map(|pack| pack.credits.as_str())
+            .filter(|credits| !credits.is_empty());
+        [locale.text(TextKey::CreditsGame)]
+            .into_iter()
+            .chain(icons)
+            .chain([locale.text(TextKey::Credits)])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Instructions followed by the controls using the current bindings
+    pub fn instructions(
+        bindings: &InputBindingsResource,
+        scheme: ControlScheme,
+        locale: &Locale,
+    ) -> String {
+        let line = |action: InputAction| {
+            format!(
+                "{} - {}",
+                bindings.label(action),
+                locale.text(TextKey::action(action, scheme))
+            )
+        };
+        [
+            locale.text(TextKey::Instructions).to_string(),
+            String::new(),
+            locale.text(TextKey::ControlsHeading).to_string(),
+            line(InputAction::MoveUp),
+            line(InputAction::MoveDown),
+            line(InputAction::MoveLeft),
+            line(InputAction::MoveRight),
+            line(InputAction::Shoot),
+            locale.text(TextKey::ZoomControl).to_string(),
+            line(InputAction::Pause),
+        ]
+        .join("\n")
+    }
 }
 
 impl Command for TitleScreen {
     fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
         world.resource_scope::<FontResource, ()>(|world, resource| {
             world.resource_scope::<AudioSettingsResource, ()>(|world, audio_settings| {
+                let instructions = Self::instructions(
+                    world.resource::<InputBindingsResource>(),
+                    world.resource::<SettingsResource>().controller_scheme,
+                    &locale,
+                );
+                let camera_shake = world.resource::<SettingsResource>().camera_shake;
+                let level_name = world
+                    .resource::<WorldLayoutResource>()
+                    .current()
+                    .name
+                    .clone();
+                let packs = world.resource::<IconPacksResource>();
+                let credits = Self::credits(packs, &locale);
+                let pack_name = packs
+                    .current()
+                    .map(|pack| pack.name.clone())
+                    .unwrap_or_default();
                 world
                     .spawn((
                         NodeBundle {
@@ -393,10 +505,11 @@ impl Command for TitleScreen {
                                 left: Val::Px(0.0),
                                 ..Default::default()
                             },
-                            background_color: Color::hex("#12141844").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                            background_color: theme.color(ThemeColor::Overlay).into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                             ..Default::default()
                         },
                         ScreenTag,
+                        ThemedBackground(ThemeColor::Overlay),
                         RenderLayers::layer(CAMERA_LAYER_UI),
                     ))
                     .with_children(|child| {
@@ -414,25 +527,27 @@ impl Command for TitleScreen {
                                         row_gap: Val::Px(16.0),
                                         ..Default::default()
                                     },
-                                    background_color: Color::hex("#121418CC").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                                    background_color: theme.color(ThemeColor::Panel).into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                     ..Default::default()
                                 },
+                                ThemedBackground(ThemeColor::Panel),
                                 RenderLayers::layer(CAMERA_LAYER_UI),
                             ))
                             .with_children(|parent| {
                                 parent.spawn((
                                     TextBundle::from_section(
-                                        if self.pause_screen {
-                                            "PAUSE"
+                                        locale.text(if self.pause_screen {
+                                            TextKey::Pause
                                         } else {
-                                            "ICON WARS!"
-                                        },
+                                            TextKey::GameTitle
+                                        }),
                                         TextStyle {
                                             font: resource.title.clone(),
                                             font_size: 128.0,
-                                            color: Color::hex("#6b9894").unwrap(),
+                                            color: theme.color(ThemeColor::Accent),
                                         },
                                     ),
+                                    ThemedText(ThemeColor::Accent),
                                     RenderLayers::layer(CAMERA_LAYER_UI),
                                 ));
 
@@ -448,77 +563,117 @@ impl Command for TitleScreen {
                                                 align_items: AlignItems::FlexStart,
                                                 ..Default::default()
                                             },
-                                            background_color: Color::hex("#121418DD")
-                                                .unwrap()
+                                            background_color: theme
+                                                .color(ThemeColor::PanelSolid)
                                                 .into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                             ..Default::default()
                                         },
+                                        ThemedBackground(ThemeColor::PanelSolid),
                                         RenderLayers::layer(CAMERA_LAYER_UI),
                                     ))
                                     .with_children(|parent| {
                                         parent.spawn((
                                             TextBundle::from_section(
-                                                Self::INSTRUCTIONS,
+                                                instructions,
                                                 TextStyle {
                                                     font: resource.text2.clone(),
                                                     font_size: 21.0,
-                                                    color: Color::hex("#adbacb").unwrap(),
+                                                    color: theme.color(ThemeColor::Text),
                                                 },
                                             )
                                             .with_text_justify(JustifyText::Center),
+                                            ThemedText(ThemeColor::Text),
                                             RenderLayers::layer(CAMERA_LAYER_UI),
                                         ));
                                     });
 
                                 if self.pause_screen {
-                                    ButtonChildBuilder {
-                                        label: "Resume Game",
-                                        kind: ButtonKind::ResumeGame,
-                                    }
-                                    .spawn(parent, &resource);
+                                    ButtonChildBuilder::new(
+                                        locale.text(TextKey::ResumeGame),
+                                        ButtonKind::ResumeGame,
+                                    )
+                                    .spawn(parent, &resource, &theme);
                                 } else {
-                                    ButtonChildBuilder {
-                                        label: "Start Game",
-                                        kind: ButtonKind::StartGame,
-                                    }
-                                    .spawn(parent, &resource);
+                                    ButtonChildBuilder::new(
+                                        locale.text(TextKey::StartGame),
+                                        ButtonKind::StartGame,
+                                    )
+                                    .spawn(parent, &resource, &theme);
+                                    ButtonChildBuilder::new(
+                                        locale.format(TextKey::Level, &[&level_name]),
+                                        ButtonKind::OpenLevelSelect,
+                                    )
+                                    .spawn(parent, &resource, &theme);
+                                    ButtonChildBuilder::new(
+                                        locale.format(TextKey::Icons, &[&pack_name]),
+                                        ButtonKind::OpenIconPackSelect,
+                                    )
+                                    .spawn(parent, &resource, &theme);
                                 }
-                                ButtonChildBuilder {
-                                    label: if audio_settings.mute_music {
-                                        "MUSIC ON"
+                                ButtonChildBuilder::new(
+                                    locale.text(TextKey::Controls),
+                                    ButtonKind::OpenControls,
+                                )
+                                .spawn(parent, &resource, &theme);
+                                ButtonChildBuilder::new(
+                                    locale.text(TextKey::Graphics),
+                                    ButtonKind::OpenGraphics,
+                                )
+                                .spawn(parent, &resource, &theme);
+                                ButtonChildBuilder::new(
+                                    locale.text(TextKey::Accessibility),
+                                    ButtonKind::OpenAccessibility,
+                                )
+                                .spawn(parent, &resource, &theme);
+                                ButtonChildBuilder::new(
+                                    locale.format(TextKey::Language, &[&locale.name]),
+                                    ButtonKind::CycleLanguage,
+                                )
+                                .spawn(parent, &resource, &theme);
+                                ButtonChildBuilder::new(
+                                    locale.text(if audio_settings.mute_music {
+                                        TextKey::MusicOn
                                     } else {
-                                        "MUSIC OFF"
-                                    },
-
-                                    kind: ButtonKind::ToggleMusic,
-                                }
-                                .spawn(parent, &resource);
-                                ButtonChildBuilder {
-                                    label: if audio_settings.mute_effects {
-                                        "SOUND ON"
+                                        TextKey::MusicOff
+                                    }),
+                                    ButtonKind::ToggleMusic,
+                                )
+                                .spawn(parent, &resource, &theme);
+                                ButtonChildBuilder::new(
+                                    locale.text(if audio_settings.mute_effects {
+                                        TextKey::SoundOn
                                     } else {
-                                        "SOUND OFF"
-                                    },
-                                    kind: ButtonKind::ToggleSound,
-                                }
-                                .spawn(parent, &resource);
+                                        TextKey::SoundOff
+                                    }),
+                                    ButtonKind::ToggleSound,
+                                )
+                                .spawn(parent, &resource, &theme);
+                                ButtonChildBuilder::new(
+                                    locale.text(if camera_shake {
+                                        TextKey::ScreenShakeOff
+                                    } else {
+                                        TextKey::ScreenShakeOn
+                                    }),
+                                    ButtonKind::ToggleScreenShake,
+                                )
+                                .spawn(parent, &resource, &theme);
 
                                 #[cfg(not(target_arch = "wasm32"))]
                                 {
                                     if !self.pause_screen {
-                                        ButtonChildBuilder {
-                                            label: "Quit Game",
-                                            kind: ButtonKind::QuitGame,
-                                        }
-                                        .spawn(parent, &resource);
+                                        ButtonChildBuilder::new(
+                                            locale.text(TextKey::QuitGame),
+                                            ButtonKind::QuitGame,
+                                        )
+                                        .spawn(parent, &resource, &theme);
                                     }
                                 }
                                 if self.pause_screen {
-                                    ButtonChildBuilder {
-                                        label: "Back to Main Menu",
-                                        kind: ButtonKind::BackToMainMenu,
-                                    }
-                                    .spawn(parent, &resource);
+                                    ButtonChildBuilder::new(
+                                        locale.text(TextKey::BackToMainMenu),
+                                        ButtonKind::BackToMainMenu,
+                                    )
+                                    .spawn(parent, &resource, &theme);
                                 }
 
                                 parent
@@ -533,23 +688,25 @@ impl Command for TitleScreen {
                                                 align_items: AlignItems::FlexStart,
                                                 ..Default::default()
                                             },
-                                            background_color: Color::hex("#121418DD")
-                                                .unwrap()
+                                            background_color: theme
+                                                .color(ThemeColor::PanelSolid)
                                                 .into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                             ..Default::default()
                                         },
+                                        ThemedBackground(ThemeColor::PanelSolid),
                                         RenderLayers::layer(CAMERA_LAYER_UI),
                                     ))
                                     .with_children(|parent| {
                                         parent.spawn((
                                             TextBundle::from_section(
-                                                Self::CREDITS,
+                                                credits,
                                                 TextStyle {
                                                     font: resource.text2.clone(),
                                                     font_size: 21.0,
-                                                    color: Color::hex("#adbacb").unwrap(),
+                                                    color: theme.color(ThemeColor::Text),
                                                 },
                                             ),
+                                            ThemedText(ThemeColor::Text),
                                             RenderLayers::layer(CAMERA_LAYER_UI),
                                         ));
                                     });
@@ -560,6 +717,681 @@ impl Command for TitleScreen {
     }
 }
 
+#[derive(Component)]
+pub struct ControlsScreenTag;
+
+/// Lists every action with its bindings, clicking a binding waits for a new input
+pub struct ControlsScreen;
+
+impl Command for ControlsScreen {
+    fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
+        world.resource_scope::<FontResource, ()>(|world, resource| {
+            let bindings = world.resource::<InputBindingsResource>().clone();
+            let rebinding = world.resource::<RebindingResource>().target;
+            let settings = world.resource::<SettingsResource>();
+            let scheme = settings.controller_scheme;
+            let rotate_camera = settings.controller_rotate_camera;
+            world
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Vw(100.0),
+                            height: Val::Vh(100.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(0.0),
+                            left: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        background_color: theme.color(ThemeColor::Overlay).into(),
+                        ..Default::default()
+                    },
+                    ScreenTag,
+                    ControlsScreenTag,
+                    ThemedBackground(ThemeColor::Overlay),
+                    RenderLayers::layer(CAMERA_LAYER_UI),
+                ))
+                .with_children(|child| {
+                    child
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    min_width: Val::Px(200.0),
+                                    padding: UiRect::all(Val::Px(32.0)),
+                                    margin: UiRect::bottom(Val::Px(64.0)),
+                                    justify_content: JustifyContent::FlexStart,
+                                    align_items: AlignItems::Center,
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(16.0),
+                                    ..Default::default()
+                                },
+                                background_color: theme.color(ThemeColor::Panel).into(),
+                                ..Default::default()
+                            },
+                            ThemedBackground(ThemeColor::Panel),
+                            RenderLayers::layer(CAMERA_LAYER_UI),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    locale.text(TextKey::ControlsTitle),
+                                    TextStyle {
+                                        font: resource.title.clone(),
+                                        font_size: 96.0,
+                                        color: theme.color(ThemeColor::Accent),
+                                    },
+                                ),
+                                ThemedText(ThemeColor::Accent),
+                                RenderLayers::layer(CAMERA_LAYER_UI),
+                            ));
+
+                            for action in InputAction::ALL {
+                                parent
+                                    .spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                align_items: AlignItems::Center,
+                                                flex_direction: FlexDirection::Row,
+                                                column_gap: Val::Px(16.0),
+                                                ..Default::default()
+                                            },
+                                            ..Default::default()
+                                        },
+                                        RenderLayers::layer(CAMERA_LAYER_UI),
+                                    ))
+                                    .with_children(|parent| {
+                                        // the fixed key is not in any of the slots
+                                        let mut name = locale
+                                            .text(TextKey::action(action, scheme))
+                                            .to_string();
+                                        if let Some(key) = action.fixed_key() {
+                                            name = format!(
+                                                "{} ({})",
+                                                name,
+                                                InputBinding::Key(key).label()
+                                            );
+                                        }
+                                        parent.spawn((
+                                            TextBundle::from_section(
+                                                name,
+                                                TextStyle {
+                                                    font: resource.text2.clone(),
+                                                    font_size: 24.0,
+                                                    color: theme.color(ThemeColor::Text),
+                                                },
+                                            )
+                                            .with_style(Style {
+                                                width: Val::Px(300.0),
+                                                ..Default::default()
+                                            }),
+                                            ThemedText(ThemeColor::Text),
+                                            RenderLayers::layer(CAMERA_LAYER_UI),
+                                        ));
+
+                                        for slot in 0..BINDING_SLOTS {
+                                            let label = if rebinding == Some((action, slot)) {
+                                                locale.text(TextKey::RebindPrompt).to_string()
+                                            } else {
+                                                bindings
+                                                    .get_slot(action, slot)
+                                                    .map(|binding| binding.label())
+                                                    .unwrap_or_else(|| "-".to_string())
+                                            };
+                                            ButtonChildBuilder::new(
+                                                label,
+                                                ButtonKind::Rebind(action, slot),
+                                            )
+                                            .with_width(240.0)
+                                            .spawn(parent, &resource, &theme);
+                                        }
+                                    });
+                            }
+
+                            ButtonChildBuilder::new(
+                                locale.format(
+                                    TextKey::Scheme,
+                                    &[&locale.text(TextKey::scheme(scheme))],
+                                ),
+                                ButtonKind::ToggleControlScheme,
+                            )
+                            .with_width(500.0)
+                            .spawn(parent, &resource, &theme);
+                            if scheme == ControlScheme::Tank {
+                                ButtonChildBuilder::new(
+                                    locale.on_off(TextKey::RotateCamera, rotate_camera),
+                                    ButtonKind::ToggleCameraRotation,
+                                )
+                                .with_width(500.0)
+                                .spawn(parent, &resource, &theme);
+                            }
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::ResetControls),
+                                ButtonKind::ResetControls,
+                            )
+                            .spawn(parent, &resource, &theme);
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::Back),
+                                ButtonKind::CloseControls,
+                            )
+                            .spawn(parent, &resource, &theme);
+                        });
+                });
+        });
+    }
+}
+
+#[derive(Component)]
+pub struct GraphicsScreenTag;
+
+/// Toggles for the post-processing effects and the icon filtering
+pub struct GraphicsScreen;
+
+impl Command for GraphicsScreen {
+    fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
+        world.resource_scope::<FontResource, ()>(|world, resource| {
+            let settings = world.resource::<SettingsResource>().clone();
+            world
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Vw(100.0),
+                            height: Val::Vh(100.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(0.0),
+                            left: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        background_color: theme.color(ThemeColor::Overlay).into(),
+                        ..Default::default()
+                    },
+                    ScreenTag,
+                    GraphicsScreenTag,
+                    ThemedBackground(ThemeColor::Overlay),
+                    RenderLayers::layer(CAMERA_LAYER_UI),
+                ))
+                .with_children(|child| {
+                    child
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    min_width: Val::Px(200.0),
+                                    padding: UiRect::all(Val::Px(32.0)),
+                                    margin: UiRect::bottom(Val::Px(64.0)),
+                                    justify_content: JustifyContent::FlexStart,
+                                    align_items: AlignItems::Center,
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(16.0),
+                                    ..Default::default()
+                                },
+                                background_color: theme.color(ThemeColor::Panel).into(),
+                                ..Default::default()
+                            },
+                            ThemedBackground(ThemeColor::Panel),
+                            RenderLayers::layer(CAMERA_LAYER_UI),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    locale.text(TextKey::GraphicsTitle),
+                                    TextStyle {
+                                        font: resource.title.clone(),
+                                        font_size: 96.0,
+                                        color: theme.color(ThemeColor::Accent),
+                                    },
+                                ),
+                                ThemedText(ThemeColor::Accent),
+                                RenderLayers::layer(CAMERA_LAYER_UI),
+                            ));
+
+                            for (label, kind) in [
+                                (
+                                    locale.format(TextKey::Theme, &[&theme.name]),
+                                    ButtonKind::CycleTheme,
+                                ),
+                                (
+                                    locale.on_off(TextKey::Bloom, settings.bloom),
+                                    ButtonKind::ToggleBloom,
+                                ),
+                                (
+                                    locale.on_off(
+                                        TextKey::DamageAberration,
+                                        settings.chromatic_aberration,
+                                    ),
+                                    ButtonKind::ToggleChromaticAberration,
+                                ),
+                                (
+                                    locale.format(
+                                        TextKey::Retro,
+                                        &[&locale
+                                            .text(TextKey::retro_filter(settings.retro_filter))],
+                                    ),
+                                    ButtonKind::CycleRetroFilter,
+                                ),
+                                (
+                                    locale.on_off(TextKey::PixelatedIcons, settings.icon_pixelated),
+                                    ButtonKind::TogglePixelated,
+                                ),
+                            ] {
+                                ButtonChildBuilder::new(label, kind)
+                                    .with_width(500.0)
+                                    .spawn(parent, &resource, &theme);
+                            }
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::Back),
+                                ButtonKind::CloseGraphics,
+                            )
+                            .spawn(parent, &resource, &theme);
+                        });
+                });
+        });
+    }
+}
+
+#[derive(Component)]
+pub struct AccessibilityScreenTag;
+
+/// Colorblind palettes, follower markers, reduced motion, text size and game speed
+pub struct AccessibilityScreen;
+
+impl AccessibilityScreen {
+    const UI_SCALE_STEPS: [f32; 4] = [1.0, 1.25, 1.5, 0.75];
+    const GAME_SPEED_STEPS: [f32; 4] = [1.0, 0.75, 0.5, 0.25];
+
+    /// The step after the one closest to `value`, wrapping around
+    fn next_step(value: f32, steps: &[f32]) -> f32 {
+        let closest = (0..steps.len())
+            .min_by(|a, b| {
+                (steps[*a] - value)
+                    .abs()
+                    .total_cmp(&(steps[*b] - value).abs())
+            })
+            .unwrap_or(0);
+        steps[(closest + 1) % steps.len()]
+    }
+
+    fn percent(value: f32) -> f32 {
+        (value * 100.0).round()
+    }
+}
+
+impl Command for AccessibilityScreen {
+    fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
+        world.resource_scope::<FontResource, ()>(|world, resource| {
+            let settings = world.resource::<SettingsResource>().clone();
+            world
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Vw(100.0),
+                            height: Val::Vh(100.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(0.0),
+                            left: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        background_color: theme.color(ThemeColor::Overlay).into(),
+                        ..Default::default()
+                    },
+                    ScreenTag,
+                    AccessibilityScreenTag,
+                    ThemedBackground(ThemeColor::Overlay),
+                    RenderLayers::layer(CAMERA_LAYER_UI),
+                ))
+                .with_children(|child| {
+                    child
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    min_width: Val::Px(200.0),
+                                    padding: UiRect::all(Val::Px(32.0)),
+                                    margin: UiRect::bottom(Val::Px(64.0)),
+                                    justify_content: JustifyContent::FlexStart,
+                                    align_items: AlignItems::Center,
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(16.0),
+                                    ..Default::default()
+                                },
+                                background_color: theme.color(ThemeColor::Panel).into(),
+                                ..Default::default()
+                            },
+                            ThemedBackground(ThemeColor::Panel),
+                            RenderLayers::layer(CAMERA_LAYER_UI),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    locale.text(TextKey::AccessibilityTitle),
+                                    TextStyle {
+                                        font: resource.title.clone(),
+                                        font_size: 96.0,
+                                        color: theme.color(ThemeColor::Accent),
+                                    },
+                                ),
+                                ThemedText(ThemeColor::Accent),
+                                RenderLayers::layer(CAMERA_LAYER_UI),
+                            ));
+
+                            for (label, kind) in [
+                                (
+                                    locale.format(
+                                        TextKey::Palette,
+                                        &[&locale
+                                            .text(TextKey::palette(settings.colorblind_palette))],
+                                    ),
+                                    ButtonKind::CycleColorblindPalette,
+                                ),
+                                (
+                                    locale.format(
+                                        TextKey::Followers,
+                                        &[&locale.text(TextKey::follower_marker(
+                                            settings.follower_marker,
+                                        ))],
+                                    ),
+                                    ButtonKind::CycleFollowerMarker,
+                                ),
+                                (
+                                    locale.on_off(TextKey::ReducedMotion, settings.reduced_motion),
+                                    ButtonKind::ToggleReducedMotion,
+                                ),
+                                (
+                                    locale.format(
+                                        TextKey::TextSize,
+                                        &[&Self::percent(settings.ui_scale)],
+                                    ),
+                                    ButtonKind::CycleUiScale,
+                                ),
+                                (
+                                    locale.format(
+                                        TextKey::GameSpeed,
+                                        &[&Self::percent(settings.game_speed)],
+                                    ),
+                                    ButtonKind::CycleGameSpeed,
+                                ),
+                            ] {
+                                ButtonChildBuilder::new(label, kind)
+                                    .with_width(500.0)
+                                    .spawn(parent, &resource, &theme);
+                            }
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::Back),
+                                ButtonKind::CloseAccessibility,
+                            )
+                            .spawn(parent, &resource, &theme);
+                        });
+                });
+        });
+    }
+}
+
+#[derive(Component)]
+pub struct LevelSelectScreenTag;
+
+/// Lists the loaded levels with their description, the current level is highlighted
+pub struct LevelSelectScreen;
+
+impl Command for LevelSelectScreen {
+    fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
+        world.resource_scope::<FontResource, ()>(|world, resource| {
+            let layouts = world.resource::<WorldLayoutResource>();
+            let current = layouts.current;
+            let levels: Vec<(String, String)> = layouts
+                .layouts
+                .iter()
+                .map(|layout| (layout.name.clone(), layout.description.clone()))
+                .collect();
+            world
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Vw(100.0),
+                            height: Val::Vh(100.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(0.0),
+                            left: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        background_color: theme.color(ThemeColor::Overlay).into(),
+                        ..Default::default()
+                    },
+                    ScreenTag,
+                    LevelSelectScreenTag,
+                    ThemedBackground(ThemeColor::Overlay),
+                    RenderLayers::layer(CAMERA_LAYER_UI),
+                ))
+                .with_children(|child| {
+                    child
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    min_width: Val::Px(200.0),
+                                    padding: UiRect::all(Val::Px(32.0)),
+                                    margin: UiRect::bottom(Val::Px(64.0)),
+                                    justify_content: JustifyContent::FlexStart,
+                                    align_items: AlignItems::Center,
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(16.0),
+                                    ..Default::default()
+                                },
+                                background_color: theme.color(ThemeColor::Panel).into(),
+                                ..Default::default()
+                            },
+                            ThemedBackground(ThemeColor::Panel),
+                            RenderLayers::layer(CAMERA_LAYER_UI),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    locale.text(TextKey::LevelsTitle),
+                                    TextStyle {
+                                        font: resource.title.clone(),
+                                        font_size: 96.0,
+                                        color: theme.color(ThemeColor::Accent),
+                                    },
+                                ),
+                                ThemedText(ThemeColor::Accent),
+                                RenderLayers::layer(CAMERA_LAYER_UI),
+                            ));
+
+                            for (index, (name, description)) in levels.into_iter().enumerate() {
+                                parent
+                                    .spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                align_items: AlignItems::Center,
+                                                flex_direction: FlexDirection::Row,
+                                                column_gap: Val::Px(16.0),
+                                                ..Default::default()
+                                            },
+                                            ..Default::default()
+                                        },
+                                        RenderLayers::layer(CAMERA_LAYER_UI),
+                                    ))
+                                    .with_children(|parent| {
+                                        let label = if index == current {
+                                            format!("> {} <", name)
+                                        } else {
+                                            name
+                                        };
+                                        ButtonChildBuilder::new(
+                                            label,
+                                            ButtonKind::SelectLevel(index),
+                                        )
+                                        .with_width(300.0)
+                                        .spawn(parent, &resource, &theme);
+                                        parent.spawn((
+                                            TextBundle::from_section(
+                                                description,
+                                                TextStyle {
+                                                    font: resource.text2.clone(),
+                                                    font_size: 21.0,
+                                                    color: theme.color(ThemeColor::Text),
+                                                },
+                                            )
+                                            .with_style(Style {
+                                                width: Val::Px(500.0),
+                                                ..Default::default()
+                                            }),
+                                            ThemedText(ThemeColor::Text),
+                                            RenderLayers::layer(CAMERA_LAYER_UI),
+                                        ));
+                                    });
+                            }
+
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::Back),
+                                ButtonKind::CloseLevelSelect,
+                            )
+                            .spawn(parent, &resource, &theme);
+                        });
+                });
+        });
+    }
+}
+
+#[derive(Component)]
+pub struct IconPackSelectScreenTag;
+
+/// Lists the loaded icon packs with their credits, the current pack is highlighted
+pub struct IconPackSelectScreen;
+
+impl Command for IconPackSelectScreen {
+    fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
+        world.resource_scope::<FontResource, ()>(|world, resource| {
+            let packs = world.resource::<IconPacksResource>();
+            let current = packs.current;
+            let packs: Vec<(String, String)> = packs
+                .packs
+                .iter()
+                .map(|pack| (pack.name.clone(), pack.credits.clone()))
+                .collect();
+            world
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Vw(100.0),
+                            height: Val::Vh(100.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(0.0),
+                            left: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        background_color: theme.color(ThemeColor::Overlay).into(),
+                        ..Default::default()
+                    },
+                    ScreenTag,
+                    IconPackSelectScreenTag,
+                    ThemedBackground(ThemeColor::Overlay),
+                    RenderLayers::layer(CAMERA_LAYER_UI),
+                ))
+                .with_children(|child| {
+                    child
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    min_width: Val::Px(200.0),
+                                    padding: UiRect::all(Val::Px(32.0)),
+                                    margin: UiRect::bottom(Val::Px(64.0)),
+                                    justify_content: JustifyContent::FlexStart,
+                                    align_items: AlignItems::Center,
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(16.0),
+                                    ..Default::default()
+                                },
+                                background_color: theme.color(ThemeColor::Panel).into(),
+                                ..Default::default()
+                            },
+                            ThemedBackground(ThemeColor::Panel),
+                            RenderLayers::layer(CAMERA_LAYER_UI),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    locale.text(TextKey::IconsTitle),
+                                    TextStyle {
+                                        font: resource.title.clone(),
+                                        font_size: 96.0,
+                                        color: theme.color(ThemeColor::Accent),
+                                    },
+                                ),
+                                ThemedText(ThemeColor::Accent),
+                                RenderLayers::layer(CAMERA_LAYER_UI),
+                            ));
+
+                            for (index, (name, credits)) in packs.into_iter().enumerate() {
+                                parent
+                                    .spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                align_items: AlignItems::Center,
+                                                flex_direction: FlexDirection::Row,
+                                                column_gap: Val::Px(16.0),
+                                                ..Default::default()
+                                            },
+                                            ..Default::default()
+                                        },
+                                        RenderLayers::layer(CAMERA_LAYER_UI),
+                                    ))
+                                    .with_children(|parent| {
+                                        let label = if index == current {
+                                            format!("> {} <", name)
+                                        } else {
+                                            name
+                                        };
+                                        ButtonChildBuilder::new(
+                                            label,
+                                            ButtonKind::SelectIconPack(index),
+                                        )
+                                        .with_width(300.0)
+                                        .spawn(parent, &resource, &theme);
+                                        parent.spawn((
+                                            TextBundle::from_section(
+                                                credits,
+                                                TextStyle {
+                                                    font: resource.text2.clone(),
+                                                    font_size: 21.0,
+                                                    color: theme.color(ThemeColor::Text),
+                                                },
+                                            )
+                                            .with_style(Style {
+                                                width: Val::Px(500.0),
+                                                ..Default::default()
+                                            }),
+                                            ThemedText(ThemeColor::Text),
+                                            RenderLayers::layer(CAMERA_LAYER_UI),
+                                        ));
+                                    });
+                            }
+
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::Back),
+                                ButtonKind::CloseIconPackSelect,
+                            )
+                            .spawn(parent, &resource, &theme);
+                        });
+                });
+        });
+    }
+}
+
 pub struct GameOverScreen {
     pub score: u32,
     pub winner: bool,
@@ -567,6 +1399,8 @@ pub struct GameOverScreen {
 
 impl Command for GameOverScreen {
     fn apply(self, world: &mut World) {
+        let theme = world.resource::<ThemeResource>().current().clone();
+        let locale = world.resource::<LocaleResource>().current().clone();
         world.resource_scope::<FontResource, ()>(|world, resource| {
             world
                 .spawn((
@@ -578,10 +1412,11 @@ impl Command for GameOverScreen {
                             align_items: AlignItems::Center,
                             ..Default::default()
                         },
-                        background_color: Color::hex("#121418DD").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                        background_color: theme.color(ThemeColor::PanelSolid).into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                         ..Default::default()
                     },
                     ScreenTag,
+                    ThemedBackground(ThemeColor::PanelSolid),
                     RenderLayers::layer(CAMERA_LAYER_UI),
                 ))
                 .with_children(|child| {
@@ -599,21 +1434,27 @@ impl Command for GameOverScreen {
                                     row_gap: Val::Px(16.0),
                                     ..Default::default()
                                 },
-                                background_color: Color::hex("#121418CC").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                                background_color: theme.color(ThemeColor::Panel).into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                 ..Default::default()
                             },
+                            ThemedBackground(ThemeColor::Panel),
                             RenderLayers::layer(CAMERA_LAYER_UI),
                         ))
                         .with_children(|parent| {
                             parent.spawn((
                                 TextBundle::from_section(
-                                    if self.winner { "YOU WIN!" } else { "GAME OVER" },
+                                    locale.text(if self.winner {
+                                        TextKey::YouWin
+                                    } else {
+                                        TextKey::GameOver
+                                    }),
                                     TextStyle {
                                         font: resource.title.clone(),
                                         font_size: 128.0,
-                                        color: Color::hex("#6b9894").unwrap(),
+                                        color: theme.color(ThemeColor::Accent),
                                     },
                                 ),
+                                ThemedText(ThemeColor::Accent),
                                 RenderLayers::layer(CAMERA_LAYER_UI),
                             ));
 
@@ -629,35 +1470,42 @@ impl Command for GameOverScreen {
                                             align_items: AlignItems::FlexStart,
                                             ..Default::default()
                                         },
-                                        background_color: Color::hex("#121418DD").unwrap().into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
+                                        background_color: theme
+                                            .color(ThemeColor::PanelSolid)
+                                            .into(), // Color::rgba(1.0, 0.0, 0.0, 0.1).into(),
                                         ..Default::default()
                                     },
+                                    ThemedBackground(ThemeColor::PanelSolid),
                                     RenderLayers::layer(CAMERA_LAYER_UI),
                                 ))
                                 .with_children(|parent| {
                                     parent.spawn((
                                         TextBundle::from_section(
+                                            locale.format(
                                                 if self.winner {
-                                                    format!("Congratulations! You collected ALL the icons!\nThat's a LOT of icons!\nThank you for playing! <3\nScore: {}", self.score)
+                                                    TextKey::WinMessage
                                                 } else {
-                                                    format!("Thank you for playing! <3\nScore: {}", self.score)
+                                                    TextKey::ThankYou
                                                 },
+                                                &[&self.score],
+                                            ),
                                             TextStyle {
                                                 font: resource.text2.clone(),
                                                 font_size: 21.0,
-                                                color: Color::hex("#adbacb").unwrap(),
+                                                color: theme.color(ThemeColor::Text),
                                             },
                                         )
-                                            .with_text_justify(JustifyText::Center),
+                                        .with_text_justify(JustifyText::Center),
+                                        ThemedText(ThemeColor::Text),
                                         RenderLayers::layer(CAMERA_LAYER_UI),
                                     ));
                                 });
 
-                            ButtonChildBuilder {
-                                label: "Back to Main Menu",
-                                kind: ButtonKind::BackToMainMenu,
-                            }
-                            .spawn(parent, &resource);
+                            ButtonChildBuilder::new(
+                                locale.text(TextKey::BackToMainMenu),
+                                ButtonKind::BackToMainMenu,
+                            )
+                            .spawn(parent, &resource, &theme);
                         });
                 });
         });
@@ -697,6 +1545,12 @@ impl Plugin for HudPlugin {
             ),
         );
 
+        app.add_systems(
+            Update,
+            update_controls_screen_system
+                .run_if(in_state(GameState::MainMenu).or_else(in_state(GameState::GamePaused))),
+        );
+
         app.add_systems(
             Update,
             (update_hud_system, show_icon_follower_added_system)
@@ -784,15 +1638,25 @@ fn enter_game_over_system(
     mut _settings: ResMut<SettingsResource>,
     screens: Query<Entity, With<ScreenTag>>,
     score: ResMut<PlayerScore>,
+    layouts: Res<WorldLayoutResource>,
     icons: Query<&IconType>,
 ) {
     for screen_entity in screens.iter() {
         commands.entity(screen_entity).despawn_recursive();
     }
 
-    let is_winner = icons
+    let captured = icons
+        .iter()
+        .filter(|icon_type| icon_type.0 == Type::Captured)
+        .count();
+    let total = icons
         .iter()
-        .all(|icon_type| icon_type.0 == Type::Player || icon_type.0 == Type::Captured);
+        .filter(|icon_type| icon_type.0 != Type::Player)
+        .count();
+    let is_winner = layouts
+        .current()
+        .win_condition
+        .is_met(score.score, captured, total);
 
     commands.add(GameOverScreen {
         score: score.score,
@@ -800,17 +1664,28 @@ fn enter_game_over_system(
     });
 }
 
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn update_button_interaction_system(
+    mut commands: Commands,
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &ButtonKind, &Children),
         (Changed<Interaction>, With<Button>),
     >,
     mut text_query: Query<&mut Text>,
     mut exit: EventWriter<AppExit>,
+    current_state: Res<State<GameState>>,
     mut state: ResMut<NextState<GameState>>,
     mut audio_settings: ResMut<AudioSettingsResource>,
+    mut bindings: ResMut<InputBindingsResource>,
+    mut rebinding: ResMut<RebindingResource>,
+    mut settings: ResMut<SettingsResource>,
+    mut layouts: ResMut<WorldLayoutResource>,
+    mut packs: ResMut<IconPacksResource>,
+    mut theme: ResMut<ThemeResource>,
+    mut locales: ResMut<LocaleResource>,
+    screens: Query<Entity, (With<ScreenTag>, Without<ScoreScreenTag>)>,
 ) {
+    let locale = locales.current().clone();
     for (interaction, mut color, button_kind, children) in &mut interaction_query {
         let mut text = text_query.get_mut(children[0]).unwrap();
 
@@ -827,29 +1702,268 @@ fn update_button_interaction_system(
                 }
                 ButtonKind::ToggleMusic => {
                     audio_settings.mute_music = !audio_settings.mute_music;
-                    text.sections[0].value = if audio_settings.mute_music {
-                        "MUSIC ON".to_string()
-                    } else {
-                        "MUSIC OFF".to_string()
-                    };
+                    text.sections[0].value = locale
+                        .text(if audio_settings.mute_music {
+                            TextKey::MusicOn
+                        } else {
+                            TextKey::MusicOff
+                        })
+                        .to_uppercase();
                 }
                 ButtonKind::ToggleSound => {
                     audio_settings.mute_effects = !audio_settings.mute_effects;
-                    text.sections[0].value = if audio_settings.mute_effects {
-                        "SOUND ON".to_string()
-                    } else {
-                        "SOUND OFF".to_string()
-                    };
+                    text.sections[0].value = locale
+                        .text(if audio_settings.mute_effects {
+                            TextKey::SoundOn
+                        } else {
+                            TextKey::SoundOff
+                        })
+                        .to_uppercase();
+                }
+                ButtonKind::ToggleScreenShake => {
+                    settings.camera_shake = !settings.camera_shake;
+                    text.sections[0].value = locale
+                        .text(if settings.camera_shake {
+                            TextKey::ScreenShakeOff
+                        } else {
+                            TextKey::ScreenShakeOn
+                        })
+                        .to_uppercase();
                 }
                 ButtonKind::QuitGame => {
                     exit.send(AppExit);
                 }
+                ButtonKind::OpenControls => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(ControlsScreen);
+                }
+                ButtonKind::CloseControls => {
+                    rebinding.target = None;
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(TitleScreen {
+                        pause_screen: *current_state.get() == GameState::GamePaused,
+                    });
+                }
+                ButtonKind::ResetControls => {
+                    rebinding.target = None;
+                    *bindings = InputBindingsResource::default();
+                    bindings.save();
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(ControlsScreen);
+                }
+                ButtonKind::ToggleControlScheme => {
+                    settings.controller_scheme = match settings.controller_scheme {
+                        ControlScheme::ScreenRelative => ControlScheme::Tank,
+                        ControlScheme::Tank => ControlScheme::ScreenRelative,
+                    };
+                    bindings.scheme = settings.controller_scheme;
+                    bindings.save();
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(ControlsScreen);
+                }
+                ButtonKind::ToggleCameraRotation => {
+                    settings.controller_rotate_camera = !settings.controller_rotate_camera;
+                    bindings.rotate_camera = settings.controller_rotate_camera;
+                    bindings.save();
+                    text.sections[0].value = locale
+                        .on_off(TextKey::RotateCamera, settings.controller_rotate_camera)
+                        .to_uppercase();
+                }
+                ButtonKind::Rebind(action, slot) => {
+                    rebinding.start(*action, *slot);
+                    text.sections[0].value = locale.text(TextKey::RebindPrompt).to_uppercase();
+                }
+                ButtonKind::OpenLevelSelect => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(LevelSelectScreen);
+                }
+                ButtonKind::CloseLevelSelect => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(TitleScreen {
+                        pause_screen: false,
+                    });
+                }
+                ButtonKind::SelectLevel(index) => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    // rebuild the world, the main menu is shown again once it is ready
+                    layouts.current = *index;
+                    state.set(GameState::GameLoading);
+                }
+                ButtonKind::OpenIconPackSelect => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(IconPackSelectScreen);
+                }
+                ButtonKind::CloseIconPackSelect => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(TitleScreen {
+                        pause_screen: false,
+                    });
+                }
+                ButtonKind::SelectIconPack(index) => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    // the texture array is rebuilt while the world is loading
+                    packs.current = *index;
+                    state.set(GameState::GameLoading);
+                }
+                ButtonKind::OpenGraphics => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(GraphicsScreen);
+                }
+                ButtonKind::CloseGraphics => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(TitleScreen {
+                        pause_screen: *current_state.get() == GameState::GamePaused,
+                    });
+                }
+                ButtonKind::ToggleBloom => {
+                    settings.bloom = !settings.bloom;
+                    text.sections[0].value =
+                        locale.on_off(TextKey::Bloom, settings.bloom).to_uppercase();
+                }
+                ButtonKind::ToggleChromaticAberration => {
+                    settings.chromatic_aberration = !settings.chromatic_aberration;
+                    text.sections[0].value = locale
+                        .on_off(TextKey::DamageAberration, settings.chromatic_aberration)
+                        .to_uppercase();
+                }
+                ButtonKind::CycleRetroFilter => {
+                    settings.retro_filter = match settings.retro_filter {
+                        RetroFilter::Off => RetroFilter::Scanlines,
+                        RetroFilter::Scanlines => RetroFilter::Crt,
+                        RetroFilter::Crt => RetroFilter::Off,
+                    };
+                    text.sections[0].value = locale
+                        .format(
+                            TextKey::Retro,
+                            &[&locale.text(TextKey::retro_filter(settings.retro_filter))],
+                        )
+                        .to_uppercase();
+                }
+                ButtonKind::CycleTheme => {
+                    // the live entities are restyled once the theme changed
+                    theme.cycle();
+                    text.sections[0].value = locale
+                        .format(TextKey::Theme, &[&theme.current().name])
+                        .to_uppercase();
+                }
+                ButtonKind::TogglePixelated => {
+                    settings.icon_pixelated = !settings.icon_pixelated;
+                    text.sections[0].value = locale
+                        .on_off(TextKey::PixelatedIcons, settings.icon_pixelated)
+                        .to_uppercase();
+                }
+                ButtonKind::OpenAccessibility => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(AccessibilityScreen);
+                }
+                ButtonKind::CloseAccessibility => {
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(TitleScreen {
+                        pause_screen: *current_state.get() == GameState::GamePaused,
+                    });
+                }
+                ButtonKind::CycleColorblindPalette => {
+                    // the theme picks up the palette and restyles the live entities
+                    settings.colorblind_palette = match settings.colorblind_palette {
+                        ColorblindPalette::Off => ColorblindPalette::RedGreen,
+                        ColorblindPalette::RedGreen => ColorblindPalette::BlueYellow,
+                        ColorblindPalette::BlueYellow => ColorblindPalette::Off,
+                    };
+                    text.sections[0].value = locale
+                        .format(
+                            TextKey::Palette,
+                            &[&locale.text(TextKey::palette(settings.colorblind_palette))],
+                        )
+                        .to_uppercase();
+                }
+                ButtonKind::CycleFollowerMarker => {
+                    settings.follower_marker = match settings.follower_marker {
+                        FollowerMarker::Ring => FollowerMarker::DashedRing,
+                        FollowerMarker::DashedRing => FollowerMarker::Badge,
+                        FollowerMarker::Badge => FollowerMarker::Ring,
+                    };
+                    text.sections[0].value = locale
+                        .format(
+                            TextKey::Followers,
+                            &[&locale.text(TextKey::follower_marker(settings.follower_marker))],
+                        )
+                        .to_uppercase();
+                }
+                ButtonKind::ToggleReducedMotion => {
+                    settings.reduced_motion = !settings.reduced_motion;
+                    text.sections[0].value = locale
+                        .on_off(TextKey::ReducedMotion, settings.reduced_motion)
+                        .to_uppercase();
+                }
+                ButtonKind::CycleUiScale => {
+                    settings.ui_scale = AccessibilityScreen::next_step(
+                        settings.ui_scale,
+                        &AccessibilityScreen::UI_SCALE_STEPS,
+                    );
+                    text.sections[0].value = locale
+                        .format(
+                            TextKey::TextSize,
+                            &[&AccessibilityScreen::percent(settings.ui_scale)],
+                        )
+                        .to_uppercase();
+                }
+                ButtonKind::CycleGameSpeed => {
+                    settings.game_speed = AccessibilityScreen::next_step(
+                        settings.game_speed,
+                        &AccessibilityScreen::GAME_SPEED_STEPS,
+                    );
+                    text.sections[0].value = locale
+                        .format(
+                            TextKey::GameSpeed,
+                            &[&AccessibilityScreen::percent(settings.game_speed)],
+                        )
+                        .to_uppercase();
+                }
+                ButtonKind::CycleLanguage => {
+                    // the screens are rebuilt with the texts and fonts of the new language
+                    locales.cycle();
+                    commands.insert_resource(locales.fonts());
+                    for screen_entity in screens.iter() {
+                        commands.entity(screen_entity).despawn_recursive();
+                    }
+                    commands.add(TitleScreen {
+                        pause_screen: *current_state.get() == GameState::GamePaused,
+                    });
+                }
             },
             Interaction::Hovered => {
-                *color = Color::hex("#3c454f").unwrap().into();
+                *color = theme.color(ThemeColor::ButtonHovered).into();
             }
             Interaction::None => {
-                *color = Color::hex("#2d333b").unwrap().into();
+                *color = theme.color(ThemeColor::Button).into();
             }
         }
     }
@@ -862,6 +1976,7 @@ fn show_icon_follower_added_system(
     mut last_shown_at: Local<Option<f32>>,
     icons: Query<&IconSheetRef>,
     screens: Query<Entity, With<FollowScreenTag>>,
+    locales: Res<LocaleResource>,
 ) {
     if let Some(last_shown_at_) = *last_shown_at {
         let duration = 0.8;
@@ -883,17 +1998,22 @@ fn show_icon_follower_added_system(
         }
 
         commands.add(FollowScreen {
-            text: format!("{} IS NOW FOLLOWING YOU", name),
+            text: locales.current().format(TextKey::NowFollowing, &[&name]),
         });
     }
 }
 
 fn toggle_game_pause_system(
-    keys: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     current_state: Res<State<GameState>>,
     mut state: ResMut<NextState<GameState>>,
+    controls_screen: Query<(), With<ControlsScreenTag>>,
 ) {
-    if keys.just_pressed(KeyCode::Escape) {
+    // the controls screen captures every input for rebinding
+    if !controls_screen.is_empty() {
+        return;
+    }
+    if input.just_pressed(InputAction::Pause) {
         if *current_state.get() == GameState::GamePaused {
             state.set(GameState::GameRunning);
         } else if *current_state.get() == GameState::GameRunning {
@@ -901,3 +2021,17 @@ fn toggle_game_pause_system(
         }
     }
 }
+
+fn update_controls_screen_system(
+    mut commands: Commands,
+    mut events: EventReader<BindingChangedEvent>,
+    screens: Query<Entity, With<ControlsScreenTag>>,
+) {
+    if events.read().count() == 0 || screens.is_empty() {
+        return;
+    }
+    for screen_entity in screens.iter() {
+        commands.entity(screen_entity).despawn_recursive();
+    }
+    commands.add(ControlsScreen);
+}
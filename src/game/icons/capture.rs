@@ -2,8 +2,11 @@ use bevy::prelude::*;
 use bevy::utils::HashSet;
 use bevy_prototype_lyon::prelude::*;
 
-use crate::game::world::WorldBoundaryResource;
-use crate::game::{settings::SettingsResource, states::GameState};
+use crate::game::controls::{ActionInput, InputAction};
+use crate::game::settings::{FollowerMarker, SettingsResource};
+use crate::game::states::GameState;
+use crate::game::theme::ThemeColor;
+use crate::game::world::{Dropzone, WorldBoundaryResource, WorldLayoutResource};
 
 use super::commands::{CircleShapeCommand, LineShapeCommand};
 use super::components::{IconFollowerCircle, IconFollowerLine, IconType, Type};
@@ -16,39 +19,41 @@ use super::{
 
 #[derive(Resource, Debug, Default)]
 struct IconCapturedGrid {
-    pub captured: Vec<Entity>,
+    /// Captured icons of each dropzone, in grid order
+    pub captured: Vec<Vec<Entity>>,
+    /// Number of grid cells, each dropzone has room for all icons
+    pub capacity: usize,
 }
 
 impl IconCapturedGrid {
-    // add captured icon to the dropzone grid and return its location and new scale
+    // add captured icon to the grid of the dropzone and return its location
     pub fn add_captured(
         &mut self,
         entity: Entity,
+        dropzone: usize,
         boundaries: &WorldBoundaryResource,
         n_icons: usize,
     ) -> Vec2 {
-        let index = self.captured.len() as i32;
-        self.captured.push(entity);
-
-        let min = boundaries.dropzone_min;
-        let max = boundaries.dropzone_max;
-        let size = max - min;
-        assert_eq!(size.x, size.y);
-        let size = size.x; // something like 2048
-        let cols = f32::sqrt(n_icons as f32).ceil() as i32;
-        let icon_size = size / cols as f32;
-        let x = (min.x + (index % cols) as f32 * icon_size) + icon_size / 2.0;
-        let y = (min.y + (cols - (index / cols) - 1) as f32 * icon_size) + icon_size / 2.0;
-
-        // let perfect_grid_offset = (cols * cols) - n_icons as i32;
-        // if perfect_grid_offset > 0 {
-        //     println!(
-        //         "perfect grid would need {} more icons!",
-        //         perfect_grid_offset
-        //     );
-        // }
-
-        // (Vec2::new(x, y), icon_size / ICON_SIZE)
+        if self.captured.len() < boundaries.dropzones.len() {
+            self.captured.resize(boundaries.dropzones.len(), Vec::new());
+        }
+        self.capacity = self.capacity.max(n_icons);
+
+        let index = self.captured[dropzone].len();
+        self.captured[dropzone].push(entity);
+
+        Self::grid_position(&boundaries.dropzones[dropzone], index, self.capacity)
+    }
+
+    /// Location of the grid cell, rows are filled from the top
+    fn grid_position(dropzone: &Dropzone, index: usize, capacity: usize) -> Vec2 {
+        let size = dropzone.size();
+        let cols = f32::sqrt(capacity as f32 * size.x / size.y).ceil().max(1.0) as usize;
+        let rows = capacity.div_ceil(cols).max(1);
+        let icon_size = (size.x / cols as f32).min(size.y / rows as f32);
+
+        let x = dropzone.min.x + (index % cols) as f32 * icon_size + icon_size / 2.0;
+        let y = dropzone.max.y - (index / cols) as f32 * icon_size - icon_size / 2.0;
         Vec2::new(x, y)
     }
 }
@@ -77,7 +82,9 @@ impl Plugin for IconCapturePlugin {
                 spawn_projectile_system,
                 update_projectiles_system,
                 update_follower_paths,
+                update_follower_markers_system,
                 player_follower_dropzone,
+                update_captured_grid_system,
             )
                 .run_if(in_state(GameState::GameRunning)),
         );
@@ -100,8 +107,7 @@ fn spawn_projectile_system(
     mut commands: Commands,
     player: Query<&IconTransform, With<IconPlayerController>>,
     followers: Res<IconFollowers>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    input: ActionInput,
     settings: Res<SettingsResource>,
     boundaries: Res<WorldBoundaryResource>,
     time: Res<Time>,
@@ -115,7 +121,7 @@ fn spawn_projectile_system(
         }
     }
 
-    if keys.just_pressed(KeyCode::Space) || mouse_button_input.just_pressed(MouseButton::Left) {
+    if input.just_pressed(InputAction::Shoot) {
         let n_projectiles = (1 + followers.followers.len()).min(20);
 
         let player = player.single();
@@ -141,14 +147,18 @@ fn spawn_projectile_system(
             let direction = Vec2::new(rotation.cos(), rotation.sin());
             let start = player.position + direction * ICON_CIRCLE_RADIUS;
 
-            events.send(ProjectileSpawnEvent);
+            events.send(ProjectileSpawnEvent {
+                position: start,
+                direction,
+            });
 
             commands.add(CircleShapeCommand {
                 radius: 8.0,
                 position: start,
                 stroke_width: 1.0,
-                color: "#7fc1bb",
-                fill_color: Some("#7fc1bb"),
+                color: ThemeColor::Projectile,
+                fill_color: Some(ThemeColor::Projectile),
+                glow: if settings.bloom { 3.0 } else { 1.0 },
                 tag: Projectile {
                     start,
                     velocity: direction * settings.projectile_speed,
@@ -172,7 +182,7 @@ fn spawn_projectile_system(
 #[allow(clippy::too_many_arguments)]
 fn update_projectiles_system(
     mut commands: Commands,
-    mut projectiles: Query<(Entity, &mut Transform, &Projectile)>,
+    mut projectiles: Query<(Entity, &mut Transform, &mut Projectile)>,
     mut icons: Query<&mut IconType>,
     index: Res<SpatialIndexResource>,
     time: Res<Time>,
@@ -182,12 +192,22 @@ fn update_projectiles_system(
     mut events: EventWriter<PlayerFollowEvent>,
 ) {
     let dt = time.delta_seconds();
-    for (entity, mut transform, Projectile { start, velocity }) in projectiles.iter_mut() {
+    for (entity, mut transform, mut projectile) in projectiles.iter_mut() {
+        let Projectile { start, velocity } = projectile.as_mut();
         transform.translation += Vec3::new(velocity.x, velocity.y, 0.0) * dt;
         let position = Vec2::new(transform.translation.x, transform.translation.y);
 
-        // despawn in dropzone
-        if boundaries.in_dropzone(position) {
+        // move the start along when wrapping, it measures the distance traveled
+        let position = {
+            let wrapped = boundaries.wrap_position(position);
+            *start += wrapped - position;
+            transform.translation.x = wrapped.x;
+            transform.translation.y = wrapped.y;
+            wrapped
+        };
+
+        // despawn in dropzone or when hitting an obstacle
+        if boundaries.in_dropzone(position) || boundaries.in_obstacle(position) {
             commands.entity(entity).despawn();
             continue;
         }
@@ -236,8 +256,12 @@ fn update_follower_paths(
     player: Query<&IconTransform, With<IconPlayerController>>,
     icons: Query<&IconTransform>,
     followers: Res<IconFollowers>,
+    boundaries: Res<WorldBoundaryResource>,
 ) {
     let player = player.single().position;
+    // lines take the short way across the edges of a wrapping world
+    let line_points =
+        |position: Vec2| get_line_points(player, player + boundaries.delta(player, position));
 
     let mut existing = HashSet::new();
 
@@ -246,7 +270,7 @@ fn update_follower_paths(
         let IconFollowerLine(entity) = line;
         if followers.followers.contains(entity) {
             let transform = icons.get(*entity).unwrap();
-            let (start, end) = get_line_points(player, transform.position);
+            let (start, end) = line_points(transform.position);
             let mut builder = GeometryBuilder::new();
             builder = builder.add(&shapes::Line(start, end));
             *path = builder.build();
@@ -270,11 +294,11 @@ fn update_follower_paths(
     for follower in &followers.followers {
         if !existing.contains(&follower) {
             let transform = icons.get(*follower).unwrap();
-            let (start, end) = get_line_points(player, transform.position);
+            let (start, end) = line_points(transform.position);
             commands.add(LineShapeCommand::<IconFollowerLine> {
                 start,
                 end,
-                color: "#884c56",
+                color: ThemeColor::Follower,
                 stroke_width: 4.0,
                 visibility: Visibility::Visible,
                 tag: IconFollowerLine(*follower),
@@ -283,22 +307,77 @@ fn update_follower_paths(
             commands.add(CircleShapeCommand::<IconFollowerCircle> {
                 radius: ICON_CIRCLE_RADIUS,
                 position: transform.position,
-                color: "#884c56",
+                color: ThemeColor::Follower,
                 fill_color: None,
                 stroke_width: 4.0,
                 visibility: Visibility::Visible,
+                glow: 1.0,
                 tag: IconFollowerCircle(*follower),
             });
         }
     }
 }
 
+/// Number of dashes of the dashed follower ring
+const MARKER_DASHES: usize = 10;
+/// Distance from the center of the badge to its corners
+const MARKER_BADGE_RADIUS: f32 = 9.0;
+
+/// Outline of the follower circle, the shape tells followers apart without their color
+fn follower_marker_path(marker: FollowerMarker) -> Path {
+    let circle = shapes::Circle {
+        radius: ICON_CIRCLE_RADIUS,
+        center: Vec2::ZERO,
+    };
+    match marker {
+        FollowerMarker::Ring => GeometryBuilder::build_as(&circle),
+        FollowerMarker::DashedRing => {
+            let mut builder = PathBuilder::new();
+            let dash = std::f32::consts::TAU / MARKER_DASHES as f32;
+            for i in 0..MARKER_DASHES {
+                // each dash covers half of its sector, as a few straight segments
+                let start = i as f32 * dash;
+                builder.move_to(Vec2::from_angle(start) * ICON_CIRCLE_RADIUS);
+                for step in 1..=4 {
+                    let angle = start + dash * 0.5 * step as f32 / 4.0;
+                    builder.line_to(Vec2::from_angle(angle) * ICON_CIRCLE_RADIUS);
+                }
+            }
+            builder.build()
+        }
+        FollowerMarker::Badge => {
+            let badge = shapes::RegularPolygon {
+                sides: 4,
+                feature: shapes::RegularPolygonFeature::Radius(MARKER_BADGE_RADIUS),
+                center: Vec2::splat(ICON_CIRCLE_RADIUS * std::f32::consts::FRAC_1_SQRT_2),
+            };
+            GeometryBuilder::new().add(&circle).add(&badge).build()
+        }
+    }
+}
+
+/// The marker only depends on its shape, the palette colors it through the theme
+fn update_follower_markers_system(
+    mut circles: Query<(&mut Path, Ref<IconFollowerCircle>)>,
+    settings: Res<SettingsResource>,
+    mut marker: Local<Option<FollowerMarker>>,
+) {
+    let changed = *marker != Some(settings.follower_marker);
+    *marker = Some(settings.follower_marker);
+    for (mut path, circle) in circles.iter_mut() {
+        if changed || circle.is_added() {
+            *path = follower_marker_path(settings.follower_marker);
+        }
+    }
+}
+
 // when player moves into drop zone / or is in the drop zone
 // all follower icons are put into the drop zone and no longer move at all,
 // arranging the icons in a grid
 #[allow(clippy::too_many_arguments)]
 fn player_follower_dropzone(
     boundaries: Res<WorldBoundaryResource>,
+    layouts: Res<WorldLayoutResource>,
     mut followers: ResMut<IconFollowers>,
     mut icons: Query<(Entity, &mut IconTransform, &mut IconType), Without<IconPlayerController>>,
     player: Query<&IconTransform, With<IconPlayerController>>,
@@ -311,13 +390,17 @@ fn player_follower_dropzone(
 ) {
     let position = player.single().position;
 
-    if boundaries.in_dropzone(position) && !followers.followers.is_empty() {
+    let Some(dropzone) = boundaries.dropzone_at(position) else {
+        return;
+    };
+    if !followers.followers.is_empty() {
         let icon_count = icons.iter().count();
         let mut n_events_sent = 0;
         for follower in followers.followers.iter() {
             let mut icon = icons.get_mut(*follower).unwrap();
+            let from = icon.1.position;
 
-            let new_position = captured.add_captured(*follower, &boundaries, icon_count);
+            let new_position = captured.add_captured(*follower, dropzone, &boundaries, icon_count);
 
             // TODO animate the position to target?
             icon.1.position = new_position; //  Vec2::ZERO; // TODO!
@@ -329,22 +412,52 @@ fn player_follower_dropzone(
                     as u32;
 
             if n_events_sent < 10 {
-                events.send(IconCaptureEvent { entity: *follower });
+                events.send(IconCaptureEvent {
+                    entity: *follower,
+                    from,
+                });
                 n_events_sent += 1;
             }
 
             // update position in spatial index, or just remove it?
             spatial_index.0.insert(*follower, new_position, Vec2::ZERO);
+        }
+        followers.followers.clear();
 
-            // check for win condition:
-            let is_winner = icons.iter().all(|(_, _, icon_type)| {
-                icon_type.0 == Type::Player || icon_type.0 == Type::Captured
-            });
-            if is_winner {
-                state.set(GameState::GameOver);
+        // check for win condition:
+        let captured_count = icons
+            .iter()
+            .filter(|(_, _, icon_type)| icon_type.0 == Type::Captured)
+            .count();
+        if layouts
+            .current()
+            .win_condition
+            .is_met(score.score, captured_count, icon_count)
+        {
+            state.set(GameState::GameOver);
+        }
+    }
+}
+
+// captured icons move along with drifting or shrinking dropzones
+fn update_captured_grid_system(
+    boundaries: Res<WorldBoundaryResource>,
+    captured: Res<IconCapturedGrid>,
+    mut icons: Query<&mut IconTransform>,
+    mut spatial_index: ResMut<SpatialIndexResource>,
+) {
+    if !boundaries.is_changed() {
+        return;
+    }
+
+    for (dropzone, entities) in boundaries.dropzones.iter().zip(captured.captured.iter()) {
+        for (index, entity) in entities.iter().enumerate() {
+            if let Ok(mut transform) = icons.get_mut(*entity) {
+                let position = IconCapturedGrid::grid_position(dropzone, index, captured.capacity);
+                transform.position = position;
+                spatial_index.0.insert(*entity, position, Vec2::ZERO);
             }
         }
-        followers.followers.clear();
     }
 }
 
@@ -5,6 +5,7 @@ use bevy::render::view::{NoFrustumCulling, RenderLayers};
 use bevy_prototype_lyon::prelude::*;
 
 use crate::game::camera::{CAMERA_LAYER, CAMERA_Z_VFX};
+use crate::game::theme::{ThemeColor, ThemePaint, ThemeResource, ThemedFill, ThemedStroke};
 
 use super::ICON_CIRCLE_RADIUS;
 
@@ -13,8 +14,10 @@ pub struct CircleShapeCommand<T> {
     pub position: Vec2,
     pub stroke_width: f32,
     pub visibility: Visibility,
-    pub color: &'static str,
-    pub fill_color: Option<&'static str>,
+    pub color: ThemeColor,
+    pub fill_color: Option<ThemeColor>,
+    /// Brightness multiplier of the colors, above 1 glows with bloom
+    pub glow: f32,
     pub tag: T,
 }
 
@@ -28,8 +31,9 @@ where
             position: Vec2::ZERO,
             stroke_width: 1.0,
             visibility: Visibility::Visible,
-            color: "#ffffff",
+            color: ThemeColor::Text,
             fill_color: None,
+            glow: 1.0,
             tag: T::default(),
         }
     }
@@ -45,7 +49,16 @@ where
             radius: self.radius,
             center: Vec2::ZERO,
         });
-        world.spawn((
+        let stroke = ThemePaint::from(self.color).with_glow(self.glow);
+        let fill = self
+            .fill_color
+            .map(|color| ThemePaint::from(color).with_glow(self.glow));
+        let theme = world.resource::<ThemeResource>().current();
+        let stroke_color = stroke.resolve(theme);
+        let fill_color = fill
+            .map(|fill| fill.resolve(theme))
+            .unwrap_or(Color::rgba(0.0, 0.0, 0.0, 0.0));
+        let mut entity = world.spawn((
             ShapeBundle {
                 path: builder.build(),
                 spatial: SpatialBundle {
@@ -59,17 +72,17 @@ where
                 },
                 ..Default::default()
             },
-            Stroke::new(Color::hex(self.color).unwrap(), self.stroke_width),
-            Fill::color(
-                self.fill_color
-                    .map(|color| Color::hex(color).unwrap())
-                    .unwrap_or(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-            ),
+            Stroke::new(stroke_color, self.stroke_width),
+            Fill::color(fill_color),
+            ThemedStroke(stroke),
             RenderLayers::layer(CAMERA_LAYER),
             self.tag,
             NoAutomaticBatching,
             NoFrustumCulling,
         ));
+        if let Some(fill) = fill {
+            entity.insert(ThemedFill(fill));
+        }
     }
 }
 
@@ -78,7 +91,7 @@ pub struct LineShapeCommand<T> {
     pub end: Vec2,
     pub stroke_width: f32,
     pub visibility: Visibility,
-    pub color: &'static str,
+    pub color: ThemeColor,
     pub tag: T,
 }
 
@@ -92,7 +105,7 @@ where
             end: Vec2::ZERO,
             stroke_width: 1.0,
             visibility: Visibility::Visible,
-            color: "#ffffff",
+            color: ThemeColor::Text,
             tag: T::default(),
         }
     }
@@ -105,6 +118,7 @@ where
     fn apply(self, world: &mut World) {
         let mut builder = GeometryBuilder::new();
         builder = builder.add(&shapes::Line(self.start, self.end));
+        let color = world.resource::<ThemeResource>().color(self.color);
         world.spawn((
             ShapeBundle {
                 path: builder.build(),
@@ -115,7 +129,8 @@ where
                 },
                 ..Default::default()
             },
-            Stroke::new(Color::hex(self.color).unwrap(), self.stroke_width),
+            Stroke::new(color, self.stroke_width),
+            ThemedStroke(self.color.into()),
             RenderLayers::layer(CAMERA_LAYER),
             NoFrustumCulling,
             self.tag,
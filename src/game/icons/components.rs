@@ -1,6 +1,8 @@
-use std::collections::BTreeMap;
+use std::ops::Range;
 
+use bevy::ecs::entity::EntityHashMap;
 use bevy::prelude::*;
+use bytemuck::{Pod, Zeroable};
 
 /// Thats a LOT of entities!
 #[derive(Component, Debug)]
@@ -39,61 +41,136 @@ pub struct IconRenderEntity;
 #[derive(Debug)]
 pub struct SheetIndex {
     pub sheet_index: u32,
-    pub tile_uv: Vec2,
+    /// Tile in the texture array layer, min corner and size in uv coordinates
+    pub uv_rect: Vec4,
+}
+
+/// Per-instance color of an icon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconTint {
+    /// Multiplied with the texture color, linear rgba
+    pub color: Vec4,
+    /// Mixes the icon towards white, 1 is fully white
+    pub highlight: f32,
+}
+
+impl Default for IconTint {
+    fn default() -> Self {
+        Self {
+            color: Vec4::ONE,
+            highlight: 0.0,
+        }
+    }
+}
+
+/// Record of one icon in the instance buffer, see `shader.wgsl`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct IconInstance {
+    /// x, y and rotation
+    pub transform: [f32; 3],
+    pub sheet_index: u32,
+    pub uv_rect: [f32; 4],
+    pub tint: [f32; 4],
+    pub highlight: f32,
+}
+
+impl IconInstance {
+    pub fn new(transform: Vec3, sheet_index: &SheetIndex, tint: IconTint) -> Self {
+        Self {
+            transform: transform.to_array(),
+            sheet_index: sheet_index.sheet_index,
+            uv_rect: sheet_index.uv_rect.to_array(),
+            tint: tint.color.to_array(),
+            highlight: tint.highlight,
+        }
+    }
 }
 
 #[derive(Component, Debug)]
 pub struct IconInstanceData {
     pub texture: Handle<Image>,
-    /// Number of instances
-    pub n_instances: u32,
-    /// Transforms of each icon, x, y and rotation.
-    /// References which sheet and the UV coordinate in the sheet
-    pub instances: BTreeMap<Entity, (Vec3, SheetIndex)>,
+    /// Dense records in the layout of the instance buffer
+    pub instances: Vec<IconInstance>,
+    /// Index of each icon in `instances`
+    pub indices: EntityHashMap<usize>,
+    /// Chunks of `INSTANCE_CHUNK` instances changed since the last upload
+    pub dirty_chunks: Vec<bool>,
+    /// The whole buffer has to be (re)allocated and uploaded
+    pub resized: bool,
 }
 
 impl IconInstanceData {
-    // vec3 (transform x, y, angle) + vec2 (uv) + uint (sheet index)
-    pub const INSTANCE_LEN: u64 = ((std::mem::size_of::<f32>() * 3)
-        + std::mem::size_of::<u32>()
-        + (std::mem::size_of::<f32>() * 2)) as u64;
+    pub const INSTANCE_LEN: u64 = std::mem::size_of::<IconInstance>() as u64;
+    /// Instances are uploaded in chunks of this size
+    pub const INSTANCE_CHUNK: usize = 256;
 
     pub fn new(texture: Handle<Image>, instances: Vec<(Entity, (Vec3, SheetIndex))>) -> Self {
+        let indices = EntityHashMap::from_iter(
+            instances
+                .iter()
+                .enumerate()
+                .map(|(index, (entity, _))| (*entity, index)),
+        );
+        let instances: Vec<_> = instances
+            .into_iter()
+            .map(|(_, (transform, sheet_index))| {
+                IconInstance::new(transform, &sheet_index, IconTint::default())
+            })
+            .collect();
         Self {
             texture,
-            n_instances: instances.len() as u32,
-            instances: BTreeMap::from_iter(instances),
+            dirty_chunks: vec![false; instances.len().div_ceil(Self::INSTANCE_CHUNK)],
+            instances,
+            indices,
+            resized: true,
         }
     }
 
-    pub fn instances_data(&self) -> Vec<u8> {
-        let mut data = Vec::new();
-        for (
-            Vec3 { x, y, z },
-            SheetIndex {
-                sheet_index,
-                tile_uv,
-            },
-        ) in self.instances.values()
-        {
-            let mut record = Vec::new();
-            record.extend_from_slice(&x.to_le_bytes());
-            record.extend_from_slice(&y.to_le_bytes());
-            record.extend_from_slice(&z.to_le_bytes());
-            record.extend_from_slice(&sheet_index.to_le_bytes());
-            record.extend_from_slice(&tile_uv.x.to_le_bytes());
-            record.extend_from_slice(&tile_uv.y.to_le_bytes());
-            data.extend_from_slice(&record);
+    pub fn update_instance(&mut self, entity: Entity, transform: Vec3, tint: IconTint) {
+        let Some(index) = self.indices.get(&entity) else {
+            panic!("Entity {:?} not found in IconInstanceData", entity);
+        };
+        let instance = &mut self.instances[*index];
+        let updated = IconInstance {
+            transform: transform.to_array(),
+            tint: tint.color.to_array(),
+            highlight: tint.highlight,
+            ..*instance
+        };
+        if *instance != updated {
+            *instance = updated;
+            self.dirty_chunks[*index / Self::INSTANCE_CHUNK] = true;
         }
-        data
     }
 
-    pub fn update_transform(&mut self, entity: Entity, transform: Vec3) {
-        if let Some(value) = self.instances.get_mut(&entity) {
-            value.0 = transform;
-        } else {
-            panic!("Entity {:?} not found in IconInstanceData", entity);
+    /// Ranges of instances to upload, neighbouring dirty chunks are merged
+    pub fn dirty_ranges(&self) -> Vec<Range<usize>> {
+        if self.resized {
+            let all = 0..self.instances.len();
+            return vec![all];
+        }
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (chunk, _) in self
+            .dirty_chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, dirty)| **dirty)
+        {
+            let start = chunk * Self::INSTANCE_CHUNK;
+            let end = (start + Self::INSTANCE_CHUNK).min(self.instances.len());
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(start..end),
+            }
         }
+        ranges
+    }
+
+    /// Called after the changes were extracted to the render world
+    pub fn clear_dirty(&mut self) {
+        self.resized = false;
+        self.dirty_chunks.fill(false);
     }
 }
 
@@ -113,6 +190,19 @@ pub struct IconTransform {
 #[derive(Component, Clone, Debug)]
 pub struct IconVelocity(pub Vec2);
 
+/// Short lived effects that change the tint of an icon
+#[derive(Component, Clone, Debug, Default)]
+pub struct IconEffects {
+    /// Fades in from 0 to 1 after spawning
+    pub fade_in: f32,
+    /// Set to 1 when hit, fades back to 0
+    pub flash: f32,
+}
+
 /// Mark entity that is controlled by the player
 #[derive(Component, Clone, Debug)]
 pub struct IconPlayerController;
+
+/// Direction the player moves in with the tank control scheme, independent of the aim rotation
+#[derive(Component, Clone, Debug, Default)]
+pub struct IconPlayerHeading(pub f32);
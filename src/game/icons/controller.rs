@@ -1,10 +1,12 @@
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
+use crate::game::controls::ActionInput;
+use crate::game::settings::ControlScheme;
 use crate::game::{camera::CameraTag, settings::SettingsResource, states::GameState};
 
 use super::{
-    components::{IconTransform, IconVelocity},
+    components::{IconPlayerHeading, IconTransform, IconVelocity},
     IconPlayerController,
 };
 
@@ -28,12 +30,34 @@ fn normalize_angle(mut angle: f32) -> f32 {
     angle
 }
 
-// player rotation by mouse position
+fn rotation_from_direction(direction: Vec2) -> f32 {
+    let rotation = direction.y.atan2(direction.x);
+    let r = std::f32::consts::PI / 2.0;
+    normalize_angle(rotation - r)
+}
+
+// player rotation by virtual aim (touch) or mouse position,
+// with the tank scheme the icon faces its heading while not aiming
 fn update_player_rotation(
     window: Query<&Window, With<PrimaryWindow>>,
     camera: Query<(&Camera, &GlobalTransform), With<CameraTag>>,
-    mut query: Query<&mut IconTransform, With<IconPlayerController>>,
+    mut query: Query<(&mut IconTransform, &IconPlayerHeading), With<IconPlayerController>>,
+    input: ActionInput,
+    settings: Res<SettingsResource>,
 ) {
+    let Ok((mut transform, heading)) = query.get_single_mut() else {
+        return;
+    };
+
+    if let Some(aim) = input.aim() {
+        // the aim stick is in screen space, the camera might be rotated
+        let (_, camera_transform) = camera.single();
+        let (_, camera_rotation, _) = camera_transform.to_scale_rotation_translation();
+        let aim = (camera_rotation * aim.extend(0.0)).truncate();
+        transform.rotation = rotation_from_direction(aim);
+        return;
+    }
+
     let (camera, camera_transform) = camera.single();
     let window = window.single();
     if let Some(world_position) = window
@@ -41,72 +65,51 @@ fn update_player_rotation(
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
         .map(|ray| ray.origin.truncate())
     {
-        if let Ok(mut transform) = query.get_single_mut() {
-            let delta = world_position - transform.position.trunc();
-            let rotation = delta.y.atan2(delta.x);
-            let r = std::f32::consts::PI / 2.0;
-            let rotation = normalize_angle(rotation - r);
-            transform.rotation = rotation;
-        }
+        let delta = world_position - transform.position.trunc();
+        transform.rotation = rotation_from_direction(delta);
+    } else if settings.controller_scheme == ControlScheme::Tank {
+        transform.rotation = heading.0;
     }
 }
 
 fn update_key_input(
     time: Res<Time>,
-    mut query: Query<(Entity, &mut IconVelocity), With<IconPlayerController>>,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut query: Query<
+        (Entity, &mut IconVelocity, &mut IconPlayerHeading),
+        With<IconPlayerController>,
+    >,
+    input: ActionInput,
     settings: Res<SettingsResource>,
 ) {
     let dt = time.delta_seconds();
     // info!("print dt = {:?}", dt);
-    if let Ok((_entity, mut velocity_)) = query.get_single_mut() {
-        // let turn = if keys.any_pressed([KeyCode::KeyQ]) {
-        //     // turn left
-        //     Some(1.0)
-        // } else if keys.any_pressed([KeyCode::KeyE]) {
-        //     // turn right
-        //     Some(-1.0)
-        // } else {
-        //     None
-        // };
-
-        // if let Some(turn) = turn {
-        //     transform.rotation += turn * dt * settings.controller_turn_speed;
-        //     transform.rotation = normalize_angle(transform.rotation);
-        // }
-
-        // let rotation = transform.rotation;
-        let rotation = 0.0; // transform.rotation;
+    if let Ok((_entity, mut velocity_, mut heading)) = query.get_single_mut() {
+        // analog input (touch) has a length below 1
+        let movement = input.movement();
 
         let r = std::f32::consts::PI / 2.0;
-        let forward_vector = Vec2::new((rotation - r).cos(), (rotation - r).sin());
-        let strafe_vector = Vec2::new(rotation.cos(), rotation.sin());
-
-        let mut accel = Vec2::ZERO;
-        if keys.any_pressed([KeyCode::ArrowUp, KeyCode::KeyW]) {
-            // forward
-            accel += forward_vector * -1.0;
-        }
-        if keys.any_pressed([KeyCode::ArrowDown, KeyCode::KeyS]) {
-            // backward
-            accel += forward_vector * 1.0;
-        }
-        if keys.any_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) {
-            // strafe left
-            accel += strafe_vector * -1.0;
-        }
-        if keys.any_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) {
-            // strafe right
-            accel += strafe_vector * 1.0;
-        }
-
-        // normalized and scaled by acceleration setting
-        let accel: Vec2 = if accel.length() > 0.0 {
-            accel.normalize() * (settings.controller_acceleration * dt)
-        } else {
-            Vec2::ZERO
+        let accel = match settings.controller_scheme {
+            ControlScheme::ScreenRelative => {
+                // forward / backward and strafe left / right, up is always up on screen
+                let rotation = 0.0;
+                let forward_vector = Vec2::new((rotation - r).cos(), (rotation - r).sin());
+                let strafe_vector = Vec2::new(rotation.cos(), rotation.sin());
+                forward_vector * -movement.y + strafe_vector * movement.x
+            }
+            ControlScheme::Tank => {
+                // left / right turn, forward / backward along the heading
+                heading.0 -= movement.x * dt * settings.controller_turn_speed;
+                heading.0 = normalize_angle(heading.0);
+
+                let rotation = heading.0;
+                let forward_vector = Vec2::new((rotation - r).cos(), (rotation - r).sin());
+                forward_vector * -movement.y
+            }
         };
 
+        // clamped and scaled by acceleration setting
+        let accel: Vec2 = accel.clamp_length_max(1.0) * (settings.controller_acceleration * dt);
+
         let mut velocity = velocity_.0;
 
         // reduce / dampen velocity
@@ -0,0 +1,120 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy::render::camera::CameraUpdateSystem;
+use bevy::render::view::RenderLayers;
+use bevy::transform::TransformSystem;
+
+use crate::game::settings::SettingsResource;
+
+use super::components::IconInstanceData;
+use super::resources::SpatialIndexResource;
+use super::ICON_SIZE;
+
+/// Icon instances drawn per frame, summed over all cameras
+pub const ICON_INSTANCE_VISIBLE: DiagnosticPath =
+    DiagnosticPath::const_new("icons/visible_instances");
+
+/// Icons in view of a camera, each camera culls on its own
+#[derive(Component, Debug, Default)]
+pub struct VisibleIconInstances {
+    /// Indices into `IconInstanceData::instances`, in buffer order
+    pub indices: Vec<u32>,
+    /// Every instance is visible, the shared instance buffer is drawn as is
+    pub all: bool,
+}
+
+pub struct IconCullingPlugin;
+
+impl Plugin for IconCullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(ICON_INSTANCE_VISIBLE));
+        app.add_systems(
+            PostUpdate,
+            cull_icon_instances_system
+                .after(TransformSystem::TransformPropagate)
+                .after(CameraUpdateSystem),
+        );
+    }
+}
+
+/// Bounding box of the camera view in world space, the camera might be rotated
+fn view_rect(transform: &GlobalTransform, projection: &OrthographicProjection) -> (Vec2, Vec2) {
+    let area = projection.area;
+    let corners = [
+        area.min,
+        area.max,
+        Vec2::new(area.min.x, area.max.y),
+        Vec2::new(area.max.x, area.min.y),
+    ]
+    .map(|corner| transform.transform_point(corner.extend(0.0)).truncate());
+    let min = corners
+        .iter()
+        .fold(Vec2::MAX, |min, corner| min.min(*corner));
+    let max = corners
+        .iter()
+        .fold(Vec2::MIN, |max, corner| max.max(*corner));
+    (min, max)
+}
+
+#[allow(clippy::type_complexity)]
+fn cull_icon_instances_system(
+    mut commands: Commands,
+    mut cameras: Query<(
+        Entity,
+        &Camera,
+        &GlobalTransform,
+        &OrthographicProjection,
+        Option<&RenderLayers>,
+        Option<&mut VisibleIconInstances>,
+    )>,
+    icons: Query<(&IconInstanceData, Option<&RenderLayers>)>,
+    index: Option<Res<SpatialIndexResource>>,
+    settings: Res<SettingsResource>,
+    mut diagnostics: Diagnostics,
+) {
+    let Ok((instance_data, icon_layers)) = icons.get_single() else {
+        return;
+    };
+    let icon_layers = icon_layers.copied().unwrap_or_default();
+    let mut n_visible = 0;
+
+    for (entity, camera, transform, projection, camera_layers, visible) in cameras.iter_mut() {
+        if !camera.is_active || !icon_layers.intersects(&camera_layers.copied().unwrap_or_default())
+        {
+            continue;
+        }
+        let Some(mut visible) = visible else {
+            // culled from the next frame on
+            commands.entity(entity).insert(VisibleIconInstances {
+                all: true,
+                ..Default::default()
+            });
+            continue;
+        };
+        visible.indices.clear();
+
+        match index.as_ref().filter(|_| settings.icon_culling) {
+            Some(index) => {
+                // icons reach into the view from neighbouring cells
+                let (min, max) = view_rect(transform, projection);
+                let margin = Vec2::splat(ICON_SIZE);
+                visible.indices.extend(
+                    index
+                        .0
+                        .query_rect(min - margin, max + margin)
+                        .filter_map(|entity| instance_data.indices.get(entity))
+                        .map(|index| *index as u32),
+                );
+                visible.indices.sort_unstable();
+                visible.all = visible.indices.len() == instance_data.instances.len();
+                n_visible += visible.indices.len();
+            }
+            None => {
+                visible.all = true;
+                n_visible += instance_data.instances.len();
+            }
+        }
+    }
+
+    diagnostics.add_measurement(&ICON_INSTANCE_VISIBLE, || n_visible as f64);
+}
@@ -8,12 +8,19 @@ pub struct PlayerFollowEvent {
 #[derive(Event, Debug)]
 pub struct PlayerDamageEvent {
     pub amount: i32,
+    /// Position of the icon that hit the player
+    pub position: Vec2,
 }
 
 #[derive(Event, Debug)]
 pub struct IconCaptureEvent {
     pub entity: Entity,
+    /// Position of the icon before it was moved into the dropzone
+    pub from: Vec2,
 }
 
 #[derive(Event, Debug)]
-pub struct ProjectileSpawnEvent;
+pub struct ProjectileSpawnEvent {
+    pub position: Vec2,
+    pub direction: Vec2,
+}
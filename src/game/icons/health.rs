@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
+use crate::game::theme::{ThemeColor, ThemeResource};
 use crate::game::world::WorldBoundaryResource;
 use crate::game::{settings::SettingsResource, states::GameState};
 
@@ -96,7 +97,10 @@ fn damage_player_system(
 
                 health.health -= damage;
 
-                events.send(PlayerDamageEvent { amount: damage });
+                events.send(PlayerDamageEvent {
+                    amount: damage,
+                    position: result.position,
+                });
 
                 if health.health <= 0 {
                     state.set(GameState::GameOver);
@@ -117,6 +121,8 @@ fn render_damage_feedback_system(
     mut player_circle: Query<&mut Stroke, With<IconPlayerCircle>>,
     time: Res<Time>,
     mut last_damage_taken_at: Local<Option<f32>>,
+    theme: Res<ThemeResource>,
+    settings: Res<SettingsResource>,
 ) {
     if let Some(last_damage_taken_at_) = *last_damage_taken_at {
         let duration = 0.4;
@@ -127,16 +133,19 @@ fn render_damage_feedback_system(
 
         if elapsed > duration {
             // reset
-            stroke.color = Color::hex("#444c56").unwrap();
+            stroke.color = theme.color(ThemeColor::Grid);
             stroke.options.line_width = 1.0;
             *last_damage_taken_at = None;
         } else {
-            stroke.color = Color::hex("#dd4c56").unwrap();
+            stroke.color = theme.color(ThemeColor::Damage);
             stroke.options.line_width = (elapsed / duration) * 10.0;
         }
     }
 
-    for PlayerDamageEvent { amount: _ } in events.read() {
-        *last_damage_taken_at = Some(time.elapsed_seconds());
+    // the circle doesn't flash with reduced motion
+    for PlayerDamageEvent { .. } in events.read() {
+        if !settings.reduced_motion {
+            *last_damage_taken_at = Some(time.elapsed_seconds());
+        }
     }
 }
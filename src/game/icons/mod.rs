@@ -7,21 +7,26 @@ use rand::prelude::*;
 use crate::game::camera::{CAMERA_LAYER, CAMERA_Z_ICONS};
 use crate::game::icons::commands::CircleShapeCommand;
 use crate::game::icons::components::{
-    IconEntity, IconInstanceData, IconPlayerCircle, IconRenderEntity, IconVelocity, SheetIndex,
+    IconEffects, IconEntity, IconInstanceData, IconPlayerCircle, IconRenderEntity, IconTint,
+    SheetIndex,
 };
+use crate::game::icons::events::PlayerFollowEvent;
 use crate::game::icons::resources::{HoveredIcon, SpatialIndexResource};
 
 use self::resources::UpdateTimer;
 
 use super::assets::icons::IconSheetAsset;
+use super::assets::IconPacksResource;
 use super::settings::SettingsResource;
 use super::states::GameState;
-use super::world::WorldBoundaryResource;
+use super::theme::{ThemeColor, ThemeResource};
+use super::world::{WorldBoundaryResource, WorldLayoutResource};
 
 mod capture;
 pub mod commands;
 mod components;
 mod controller;
+mod culling;
 pub mod events;
 pub mod health;
 mod renderer;
@@ -29,14 +34,30 @@ mod resources;
 mod roaming;
 mod spatial;
 
-pub use components::{IconPlayerController, IconSheetRef, IconTransform, IconType, Type};
+pub use capture::IconFollowers;
+pub use components::{
+    IconPlayerController, IconPlayerHeading, IconSheetRef, IconTransform, IconType, IconVelocity,
+    Type,
+};
+pub use culling::ICON_INSTANCE_VISIBLE;
+pub use renderer::{ICON_INSTANCE_ALLOCATIONS, ICON_INSTANCE_UPLOAD};
 pub use resources::IconSheetResource;
 
 pub const ICON_SIZE: f32 = 32.0;
 pub const ICON_CIRCLE_RADIUS: f32 = ICON_SIZE / 2.0 + 8.0;
 pub const ICON_MIN_DISTANCE: f32 = 45.25 + 15.0;
+/// Random positions tried before a spawn region counts as full
+const MAX_SPAWN_ATTEMPTS: usize = 64;
+/// Positions tried for an icon before it is skipped, the spawn regions might be too small
+const MAX_PLACEMENT_ATTEMPTS: usize = 256;
 pub const SPATIAL_GRID_SIZE: f32 = 128.0; // TODO: huge performance impact, tune this later!
 
+/// Seconds for icons to fade in after spawning
+const ICON_FADE_IN_TIME: f32 = 0.6;
+/// Seconds for the flash of an icon that was hit to fade out
+const ICON_FLASH_TIME: f32 = 0.35;
+const ICON_ALPHA_CAPTURED: f32 = 0.6;
+
 pub struct IconPlugin;
 
 impl Plugin for IconPlugin {
@@ -45,6 +66,7 @@ impl Plugin for IconPlugin {
         app.insert_resource(UpdateTimer::default());
         app.add_plugins((
             renderer::IconRendererPlugin,
+            culling::IconCullingPlugin,
             roaming::IconRoamingPlugin,
             controller::IconPlayerControllerPlugin,
             capture::IconCapturePlugin,
@@ -55,6 +77,7 @@ impl Plugin for IconPlugin {
             Update,
             (
                 apply_icon_velocity,
+                update_icon_effects_system,
                 update_icon_instance_data,
                 fix_free_items_in_dropzone,
             )
@@ -64,16 +87,29 @@ impl Plugin for IconPlugin {
     }
 }
 
-fn random_position_in_bounds(rng: &mut ThreadRng, boundaries: &WorldBoundaryResource) -> Vec2 {
-    loop {
-        let position = Vec2::new(
-            rng.gen_range(boundaries.bounds_min.x..boundaries.bounds_max.x),
-            rng.gen_range(boundaries.bounds_min.y..boundaries.bounds_max.y),
-        );
-        if !boundaries.in_dropzone(position) {
-            return position;
-        }
-    }
+/// Random position in a spawn region outside of dropzones and obstacles, none if no such
+/// position was found after a few attempts
+fn random_position_in_bounds(
+    rng: &mut ThreadRng,
+    boundaries: &WorldBoundaryResource,
+) -> Option<Vec2> {
+    (0..MAX_SPAWN_ATTEMPTS).find_map(|_| {
+        // pick a spawn region by area, or anywhere in the world without any
+        let (min, max) = boundaries
+            .spawn_regions
+            .choose_weighted(rng, |region| region.size.x * region.size.y)
+            .map(|region| {
+                (
+                    region.min().max(boundaries.bounds_min),
+                    region.max().min(boundaries.bounds_max),
+                )
+            })
+            .ok()
+            .filter(|(min, max)| min.cmplt(*max).all())
+            .unwrap_or((boundaries.bounds_min, boundaries.bounds_max));
+        let position = Vec2::new(rng.gen_range(min.x..max.x), rng.gen_range(min.y..max.y));
+        (!boundaries.in_dropzone(position) && !boundaries.in_obstacle(position)).then_some(position)
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -83,6 +119,8 @@ fn init_icons_system(
     assets: Res<Assets<IconSheetAsset>>,
     mut meshes: ResMut<Assets<Mesh>>,
     boundaries: Res<WorldBoundaryResource>,
+    layouts: Res<WorldLayoutResource>,
+    packs: Res<IconPacksResource>,
 
     player_circle: Query<Entity, With<IconPlayerCircle>>,
     existing_icons: Query<Entity, With<IconEntity>>,
@@ -103,19 +141,28 @@ fn init_icons_system(
     let WorldBoundaryResource {
         bounds_min,
         bounds_max,
+        wrap,
         ..
     } = boundaries.as_ref();
+    let layout = layouts.current();
+    // the pack might replace the player icon of the level
+    let player_icon = packs
+        .current()
+        .and_then(|pack| pack.player_icon.as_ref())
+        .unwrap_or(&layout.player_icon);
     let IconSheetAsset(sheets) = assets.get(&resource.handle).unwrap();
     let mut rng = rand::thread_rng();
     let mut instances = Vec::new();
     let mut positions = Vec::new();
     let mut textures = Vec::new();
     let mut count = 0;
+    let mut skipped = 0;
 
     let mut player_position = Vec2::ZERO;
     let mut player_ = false;
 
-    let mut spatial_index = spatial::SpatialIndex::new(*bounds_min, *bounds_max, SPATIAL_GRID_SIZE);
+    let mut spatial_index =
+        spatial::SpatialIndex::new(*bounds_min, *bounds_max, SPATIAL_GRID_SIZE, *wrap);
 
     sheets.iter().enumerate().for_each(|(sheet_index, sheet)| {
         textures.push(sheet.handle.clone());
@@ -124,93 +171,95 @@ fn init_icons_system(
             .iter()
             .enumerate()
             .for_each(|(icon_index, icon)| {
-                loop {
-                    // candidate:
-                    let mut position = random_position_in_bounds(&mut rng, &boundaries);
-
-                    // search for collisions:
-                    let mut collision = false;
-                    for other_position in &positions {
-                        let d: Vec2 = position - *other_position;
-                        if d.length() < ICON_MIN_DISTANCE {
-                            collision = true;
-                            break;
-                        }
-                    }
-                    // try again if colliding with some other icon
-                    if collision {
-                        continue;
-                    }
-
-                    let mut rotation = (rng.gen_range(0.0..360.0) as f32).to_radians();
-
-                    // let is_player = icon_index == 0 && !player_; //
-                    let is_player = icon.name == "rust";
-                    if is_player {
-                        player_ = true;
-                        position = Vec2::new(
-                            0.0,
-                            (boundaries.dropzone_max.y - boundaries.dropzone_min.y) * 0.25,
-                        );
-                        rotation = 0.0;
-                        player_position = position;
-                    }
-
-                    if !is_player && (settings.max_icons != 0 && count > settings.max_icons) {
-                        break;
-                    }
-
-                    let initial_speed = 1.0;
-                    let velocity = if is_player {
-                        Vec2::ZERO
-                    } else {
-                        Vec2::new(
-                            rotation.cos() * initial_speed,
-                            rotation.sin() * initial_speed,
-                        )
+                // the player icon is always spawned, even if the level filters it out
+                let is_player = icon.name == *player_icon;
+                if !is_player && !layout.icons.matches(&icon.name) {
+                    return;
+                }
+                if !is_player && (settings.max_icons != 0 && count > settings.max_icons) {
+                    return;
+                }
+                let (position, rotation) = if is_player {
+                    player_ = true;
+                    player_position = boundaries.player_start;
+                    (boundaries.player_start, 0.0)
+                } else {
+                    // candidates keep their distance to the other icons
+                    let free = (0..MAX_PLACEMENT_ATTEMPTS).find_map(|_| {
+                        random_position_in_bounds(&mut rng, &boundaries).filter(|position| {
+                            positions
+                                .iter()
+                                .all(|other: &Vec2| position.distance(*other) >= ICON_MIN_DISTANCE)
+                        })
+                    });
+                    let Some(position) = free else {
+                        skipped += 1;
+                        return;
                     };
+                    (position, (rng.gen_range(0.0..360.0) as f32).to_radians())
+                };
+
+                let initial_speed = 1.0;
+                let velocity = if is_player {
+                    Vec2::ZERO
+                } else {
+                    Vec2::new(
+                        rotation.cos() * initial_speed,
+                        rotation.sin() * initial_speed,
+                    )
+                };
+
+                let entity = commands
+                    .spawn((
+                        IconEntity,
+                        IconSheetRef {
+                            sheet_index,
+                            icon_index,
+                            icon_name: icon.name.clone(),
+                        },
+                        IconTransform { position, rotation },
+                        IconVelocity(velocity),
+                        IconType(if is_player { Type::Player } else { Type::Free }),
+                        IconEffects::default(),
+                    ))
+                    .id();
+
+                // perhaps use the bevy icon instead?
+                if is_player {
+                    commands
+                        .entity(entity)
+                        .insert((IconPlayerController, IconPlayerHeading::default()));
+                }
 
-                    let entity = commands
-                        .spawn((
-                            IconEntity,
-                            IconSheetRef {
-                                sheet_index,
-                                icon_index,
-                                icon_name: icon.name.clone(),
-                            },
-                            IconTransform { position, rotation },
-                            IconVelocity(velocity),
-                            IconType(if is_player { Type::Player } else { Type::Free }),
-                        ))
-                        .id();
-
-                    // perhaps use the bevy icon instead?
-                    if is_player {
-                        commands.entity(entity).insert(IconPlayerController);
-                    }
-
-                    spatial_index.insert(entity, position, velocity);
-
-                    let sheet_index = SheetIndex {
-                        sheet_index: sheet_index as u32,
-                        tile_uv: Vec2::new(
-                            icon.x as f32, // / sheet.width as f32,
-                            icon.y as f32, // / sheet.height as f32,
-                        ),
-                    };
+                spatial_index.insert(entity, position, velocity);
 
-                    positions.push(position);
+                let sheet_index = SheetIndex {
+                    sheet_index: sheet_index as u32,
+                    uv_rect: Vec4::new(
+                        icon.x as f32,
+                        icon.y as f32,
+                        sheet.tile_width as f32,
+                        sheet.tile_height as f32,
+                    ) / resource.texture_array_size.xyxy(),
+                };
 
-                    instances.push((
-                        entity,
-                        (Vec3::new(position.x, position.y, rotation), sheet_index),
-                    ));
+                positions.push(position);
 
-                    count += 1;
-                    break;
-                }
+                instances.push((
+                    entity,
+                    (Vec3::new(position.x, position.y, rotation), sheet_index),
+                ));
+
+                count += 1;
             });
     });
+    if skipped > 0 {
+        warn!(
+            "No room left in the spawn regions, skipped {} of {} icons",
+            skipped,
+            count + skipped
+        );
+    }
     // info!("Fitted {} icons in {:?}", count, start.elapsed());
 
     // info!("Spawned {} icons", count);
@@ -228,6 +277,7 @@ fn init_icons_system(
         },
         RenderLayers::layer(CAMERA_LAYER),
         IconInstanceData::new(resource.texture_array.clone().unwrap(), instances),
+        // the instances are culled one by one, see `culling.rs`
         NoFrustumCulling,
         NoAutomaticBatching,
     ));
@@ -235,7 +285,7 @@ fn init_icons_system(
     commands.add(CircleShapeCommand::<IconPlayerCircle> {
         radius: ICON_CIRCLE_RADIUS,
         position: player_position,
-        color: "#444c56",
+        color: ThemeColor::Grid,
         tag: IconPlayerCircle,
         ..Default::default()
     });
@@ -245,13 +295,67 @@ fn init_icons_system(
     // state.set(GameState::MainMenu);
 }
 
+fn update_icon_effects_system(
+    mut query: Query<&mut IconEffects>,
+    mut follow_events: EventReader<PlayerFollowEvent>,
+    settings: Res<SettingsResource>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for mut effects in query.iter_mut() {
+        // skip idle icons, so they are not marked as changed
+        if effects.fade_in < 1.0 {
+            effects.fade_in = (effects.fade_in + dt / ICON_FADE_IN_TIME).min(1.0);
+        }
+        if effects.flash > 0.0 {
+            effects.flash = (effects.flash - dt / ICON_FLASH_TIME).max(0.0);
+        }
+    }
+
+    // icons flash when hit by a projectile
+    for PlayerFollowEvent { entity } in follow_events.read() {
+        if settings.reduced_motion {
+            continue;
+        }
+        if let Ok(mut effects) = query.get_mut(*entity) {
+            effects.flash = 1.0;
+        }
+    }
+}
+
+/// Followers are tinted and captured icons dimmed, `tints` are the linear
+/// icon, follower and captured colors
+fn icon_tint(icon_type: Type, effects: &IconEffects, tints: &[Vec4; 3]) -> IconTint {
+    let (color, alpha) = match icon_type {
+        Type::Player | Type::Free => (tints[0], 1.0),
+        Type::Follower => (tints[1], 1.0),
+        Type::Captured => (tints[2], ICON_ALPHA_CAPTURED),
+    };
+    let fade_in = effects.fade_in * effects.fade_in * (3.0 - 2.0 * effects.fade_in);
+    IconTint {
+        color: color.truncate().extend(alpha * fade_in),
+        highlight: effects.flash,
+    }
+}
+
 fn update_icon_instance_data(
-    query: Query<(Entity, &IconTransform)>,
+    query: Query<(Entity, &IconTransform, &IconType, &IconEffects)>,
     mut instance_data: Query<&mut IconInstanceData>,
+    theme: Res<ThemeResource>,
 ) {
     let mut instance_data = instance_data.get_single_mut().unwrap();
-    for (entity, IconTransform { position, rotation }) in &query {
-        instance_data.update_transform(entity, Vec3::new(position.x, position.y, *rotation));
+    let tints = [
+        ThemeColor::IconTint,
+        ThemeColor::FollowerTint,
+        ThemeColor::CapturedTint,
+    ]
+    .map(|color| theme.current().linear(color));
+    for (entity, IconTransform { position, rotation }, icon_type, effects) in &query {
+        instance_data.update_instance(
+            entity,
+            Vec3::new(position.x, position.y, *rotation),
+            icon_tint(icon_type.0, effects, &tints),
+        );
     }
 }
 
@@ -271,9 +375,25 @@ pub fn apply_icon_velocity(
         let new_position =
             position.position + velocity.0 * (time.delta_seconds() * settings.velocity_time_scale);
 
-        if !boundaries.in_bounds(new_position) {
+        // re-enter on the opposite side or bounce off the edge
+        let new_position = if boundaries.wrap {
+            boundaries.wrap_position(new_position)
+        } else if !boundaries.in_bounds(new_position) {
             velocity.0 = -velocity.0;
             continue;
+        } else {
+            new_position
+        };
+
+        // slide along obstacles instead of moving through them
+        let mut new_position = new_position;
+        for obstacle in boundaries.obstacles.iter() {
+            if let Some(normal) = obstacle.resolve_collision(&mut new_position, ICON_SIZE / 2.0) {
+                let into = velocity.0.dot(normal);
+                if into < 0.0 {
+                    velocity.0 -= normal * into;
+                }
+            }
         }
 
         position.position = new_position;
@@ -314,7 +434,10 @@ pub fn fix_free_items_in_dropzone(
     let mut rng = rand::thread_rng();
     for (entity, mut transform, velocity, icon_type) in query.iter_mut() {
         if icon_type.0 == Type::Free && boundaries.in_dropzone(transform.position) {
-            let new_position = random_position_in_bounds(&mut rng, &boundaries);
+            // stays in the dropzone until a free position turns up
+            let Some(new_position) = random_position_in_bounds(&mut rng, &boundaries) else {
+                continue;
+            };
 
             transform.position = new_position;
             spatial_index.0.insert(entity, new_position, velocity.0);
@@ -1,9 +1,9 @@
 use bevy::core_pipeline::core_2d::Transparent2d;
-use bevy::ecs::query::QueryItem;
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::ecs::entity::EntityHashMap;
 use bevy::ecs::system::lifetimeless::*;
 use bevy::ecs::system::SystemParamItem;
 use bevy::prelude::*;
-use bevy::render::extract_component::{ExtractComponent, ExtractComponentPlugin};
 use bevy::render::mesh::{GpuBufferInfo, MeshVertexBufferLayout};
 use bevy::render::render_asset::RenderAssets;
 use bevy::render::render_phase::AddRenderCommand;
@@ -13,52 +13,68 @@ use bevy::render::render_phase::{
 };
 
 use bevy::render::render_resource::*;
-use bevy::render::renderer::RenderDevice;
+use bevy::render::renderer::{RenderDevice, RenderQueue};
 use bevy::render::view::{ExtractedView, VisibleEntities};
-use bevy::render::{Render, RenderApp, RenderSet};
+use bevy::render::{Extract, ExtractSchedule, Render, RenderApp, RenderSet};
 use bevy::sprite::{
     Mesh2dPipeline, Mesh2dPipelineKey, RenderMesh2dInstance, RenderMesh2dInstances,
     SetMesh2dBindGroup, SetMesh2dViewBindGroup,
 };
 use bevy::utils::FloatOrd;
 
-use super::components::IconInstanceData;
+use super::components::{IconInstance, IconInstanceData};
+use super::culling::VisibleIconInstances;
+use crate::game::settings::SettingsResource;
 
+/// Bytes of icon instances written to the shared instance buffer per frame,
+/// views that cull icons write their visible instances on top of that
+pub const ICON_INSTANCE_UPLOAD: DiagnosticPath =
+    DiagnosticPath::const_new("icons/instance_upload_bytes");
+/// Instance buffers allocated per frame, only when the number of icons changes
+pub const ICON_INSTANCE_ALLOCATIONS: DiagnosticPath =
+    DiagnosticPath::const_new("icons/instance_buffer_allocations");
+
+/// Changes of the instances since the last frame, the instance buffer itself
+/// stays in the render world
 #[derive(Component, Debug)]
-pub struct GpuIconInstanceData {
-    pub data: Vec<u8>,
+pub struct ExtractedIconInstances {
     pub n_instances: usize,
     pub texture: Handle<Image>,
+    /// Sample the texture without filtering
+    pub pixelated: bool,
+    /// Index of the first instance and the records to write there
+    pub writes: Vec<(usize, Vec<IconInstance>)>,
 }
 
-impl ExtractComponent for IconInstanceData {
-    type QueryData = &'static IconInstanceData;
-    type QueryFilter = ();
-    type Out = GpuIconInstanceData;
-
-    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<GpuIconInstanceData> {
-        Some(GpuIconInstanceData {
-            data: item.instances_data(),
-            n_instances: item.n_instances as usize,
-            texture: item.texture.clone(),
-        })
-    }
+/// Icons in view of a camera, `None` if all of them are visible
+#[derive(Component, Debug)]
+pub struct ExtractedVisibleIcons {
+    pub instances: Option<Vec<IconInstance>>,
 }
 
 pub struct IconRendererPlugin;
 
 impl Plugin for IconRendererPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ExtractComponentPlugin::<IconInstanceData>::default());
+        app.register_diagnostic(Diagnostic::new(ICON_INSTANCE_UPLOAD).with_suffix(" B"));
+        app.register_diagnostic(Diagnostic::new(ICON_INSTANCE_ALLOCATIONS));
+        app.add_systems(First, clear_icon_instance_changes);
         app.sub_app_mut(RenderApp)
             .add_render_command::<Transparent2d, DrawCustom>()
             .init_resource::<SpecializedMeshPipelines<CustomPipeline>>()
             .init_resource::<TextureBindGroup>()
+            .init_resource::<InstanceBuffers>()
+            .init_resource::<ViewInstanceBuffers>()
+            .add_systems(
+                ExtractSchedule,
+                (extract_icon_instances, extract_visible_icons),
+            )
             .add_systems(
                 Render,
                 (
                     queue_custom.in_set(RenderSet::QueueMeshes),
                     prepare_instance_buffers.in_set(RenderSet::PrepareResources),
+                    prepare_view_instance_buffers.in_set(RenderSet::PrepareResources),
                     prepare_texture_bind_group.in_set(RenderSet::PrepareBindGroups),
                 ),
             );
@@ -85,8 +101,9 @@ pub fn queue_custom(
         &VisibleEntities,
         &mut RenderPhase<Transparent2d>,
         &ExtractedView,
+        Option<&ExtractedVisibleIcons>,
     )>,
-    query: Query<&GpuIconInstanceData>,
+    query: Query<&ExtractedIconInstances>,
 ) {
     if render_mesh_instances.is_empty() {
         return;
@@ -95,7 +112,14 @@ pub fn queue_custom(
         return;
     }
     // Iterate each view (a camera is a view)
-    for (visible_entities, mut transparent_phase, view) in &mut views {
+    for (visible_entities, mut transparent_phase, view, visible_icons) in &mut views {
+        // every icon was culled
+        if visible_icons
+            .and_then(|visible_icons| visible_icons.instances.as_ref())
+            .is_some_and(|instances| instances.is_empty())
+        {
+            continue;
+        }
         let draw_colored_mesh2d = transparent_draw_functions.read().id::<DrawCustom>();
 
         let mesh_key = Mesh2dPipelineKey::from_msaa_samples(msaa.samples())
@@ -146,27 +170,162 @@ pub fn queue_custom(
     }
 }
 
-#[derive(Component)]
+/// The extraction only copies the changed instances, measure how much that is
+fn clear_icon_instance_changes(
+    mut query: Query<&mut IconInstanceData>,
+    mut diagnostics: Diagnostics,
+) {
+    let mut upload = 0;
+    let mut allocations = 0;
+    for mut instance_data in query.iter_mut() {
+        upload += instance_data
+            .dirty_ranges()
+            .iter()
+            .map(|range| range.len() as u64 * IconInstanceData::INSTANCE_LEN)
+            .sum::<u64>();
+        if instance_data.resized {
+            allocations += 1;
+        }
+        instance_data.clear_dirty();
+    }
+    diagnostics.add_measurement(&ICON_INSTANCE_UPLOAD, || upload as f64);
+    diagnostics.add_measurement(&ICON_INSTANCE_ALLOCATIONS, || allocations as f64);
+}
+
+fn extract_icon_instances(
+    mut commands: Commands,
+    query: Extract<Query<(Entity, &IconInstanceData)>>,
+    settings: Extract<Res<SettingsResource>>,
+) {
+    for (entity, instance_data) in &query {
+        let writes = instance_data
+            .dirty_ranges()
+            .into_iter()
+            .map(|range| (range.start, instance_data.instances[range].to_vec()))
+            .collect();
+        commands
+            .get_or_spawn(entity)
+            .insert(ExtractedIconInstances {
+                n_instances: instance_data.instances.len(),
+                texture: instance_data.texture.clone(),
+                pixelated: settings.icon_pixelated,
+                writes,
+            });
+    }
+}
+
+fn extract_visible_icons(
+    mut commands: Commands,
+    views: Extract<Query<(Entity, &VisibleIconInstances)>>,
+    icons: Extract<Query<&IconInstanceData>>,
+) {
+    let Ok(instance_data) = icons.get_single() else {
+        return;
+    };
+    for (entity, visible) in &views {
+        let instances = (!visible.all).then(|| {
+            visible
+                .indices
+                .iter()
+                .map(|index| instance_data.instances[*index as usize])
+                .collect()
+        });
+        commands
+            .get_or_spawn(entity)
+            .insert(ExtractedVisibleIcons { instances });
+    }
+}
+
 pub struct InstanceBuffer {
     buffer: Buffer,
     length: usize,
 }
 
+/// Instance buffers of the render entities, kept across frames
+#[derive(Resource, Default)]
+pub struct InstanceBuffers(EntityHashMap<InstanceBuffer>);
+
+/// Visible instances of each view that culled some of the icons, the
+/// buffers only grow
+#[derive(Resource, Default)]
+pub struct ViewInstanceBuffers(EntityHashMap<InstanceBuffer>);
+
 fn prepare_instance_buffers(
-    mut commands: Commands,
-    query: Query<(Entity, &GpuIconInstanceData)>,
+    query: Query<(Entity, &ExtractedIconInstances)>,
+    mut instance_buffers: ResMut<InstanceBuffers>,
     render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
 ) {
-    for (entity, instance_data) in &query {
-        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
-            label: Some("instance data buffer"),
-            contents: &instance_data.data,
-            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
-        });
-        commands.entity(entity).insert(InstanceBuffer {
-            buffer,
-            length: instance_data.n_instances,
-        });
+    // the render entity is respawned for a new game
+    instance_buffers
+        .0
+        .retain(|entity, _| query.get(*entity).is_ok_and(|(_, e)| e.n_instances > 0));
+
+    for (entity, extracted) in &query {
+        if extracted.n_instances == 0 {
+            continue;
+        }
+        // only allocate when the number of icons changes
+        if !matches!(instance_buffers.0.get(&entity), Some(instance_buffer) if instance_buffer.length == extracted.n_instances)
+        {
+            let buffer = render_device.create_buffer(&BufferDescriptor {
+                label: Some("instance data buffer"),
+                size: extracted.n_instances as u64 * IconInstanceData::INSTANCE_LEN,
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            instance_buffers.0.insert(
+                entity,
+                InstanceBuffer {
+                    buffer,
+                    length: extracted.n_instances,
+                },
+            );
+        }
+        let instance_buffer = &instance_buffers.0[&entity];
+
+        for (start, instances) in &extracted.writes {
+            render_queue.write_buffer(
+                &instance_buffer.buffer,
+                *start as u64 * IconInstanceData::INSTANCE_LEN,
+                bytemuck::cast_slice(instances),
+            );
+        }
+    }
+}
+
+fn prepare_view_instance_buffers(
+    views: Query<(Entity, &ExtractedVisibleIcons)>,
+    mut view_buffers: ResMut<ViewInstanceBuffers>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    view_buffers.0.retain(|entity, _| {
+        views
+            .get(*entity)
+            .is_ok_and(|(_, visible)| visible.instances.is_some())
+    });
+
+    for (entity, visible) in &views {
+        let Some(instances) = visible.instances.as_ref().filter(|i| !i.is_empty()) else {
+            continue;
+        };
+        let size = instances.len() as u64 * IconInstanceData::INSTANCE_LEN;
+        if !matches!(view_buffers.0.get(&entity), Some(view_buffer) if view_buffer.buffer.size() >= size)
+        {
+            let buffer = render_device.create_buffer(&BufferDescriptor {
+                label: Some("view instance data buffer"),
+                size: size.next_power_of_two(),
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            view_buffers
+                .0
+                .insert(entity, InstanceBuffer { buffer, length: 0 });
+        }
+        let view_buffer = view_buffers.0.get_mut(&entity).unwrap();
+        view_buffer.length = instances.len();
+        render_queue.write_buffer(&view_buffer.buffer, 0, bytemuck::cast_slice(instances));
     }
 }
 
@@ -177,7 +336,7 @@ pub struct TextureBindGroup {
 
 pub fn prepare_texture_bind_group(
     render_device: Res<RenderDevice>,
-    instance: Query<Option<&GpuIconInstanceData>>,
+    instance: Query<Option<&ExtractedIconInstances>>,
     images: Res<RenderAssets<Image>>,
     pipeline: Res<CustomPipeline>,
     mut texture_bind_group: ResMut<TextureBindGroup>,
@@ -194,7 +353,11 @@ pub fn prepare_texture_bind_group(
                 },
                 BindGroupEntry {
                     binding: 1,
-                    resource: BindingResource::Sampler(&gpu_image.sampler),
+                    resource: BindingResource::Sampler(if instance.pixelated {
+                        &pipeline.sampler_nearest
+                    } else {
+                        &pipeline.sampler_linear
+                    }),
                 },
             ],
         );
@@ -207,6 +370,10 @@ pub struct CustomPipeline {
     shader: Handle<Shader>,
     mesh_pipeline: Mesh2dPipeline,
     texture_bind_group_layout: BindGroupLayout,
+    /// Trilinear filtering through the mip chain of the texture array
+    sampler_linear: Sampler,
+    /// The pixel-crisp look, without mipmaps
+    sampler_nearest: Sampler,
 }
 
 impl FromWorld for CustomPipeline {
@@ -235,11 +402,28 @@ impl FromWorld for CustomPipeline {
             Some("icons_texture_array"),
             &[texture_uniform_layout_entry, texture_sampler_layout_entry],
         );
+        let sampler_linear = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("icons_sampler_linear"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+        let sampler_nearest = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("icons_sampler_nearest"),
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            lod_max_clamp: 0.0,
+            ..Default::default()
+        });
 
         CustomPipeline {
             shader,
             mesh_pipeline: Mesh2dPipeline::from_world(world),
             texture_bind_group_layout,
+            sampler_linear,
+            sampler_nearest,
         }
     }
 }
@@ -276,12 +460,24 @@ impl SpecializedMeshPipeline for CustomPipeline {
                     offset: (3 * 4),
                     shader_location: 4, // shader locations 0-2 are taken up by Position, Normal and UV attributes
                 },
-                // uv coordinate offset in icon sheet
+                // uv rect of the tile in the icon sheet, min corner and size
                 VertexAttribute {
-                    format: VertexFormat::Float32x2,
+                    format: VertexFormat::Float32x4,
                     offset: (3 * 4 + 4),
                     shader_location: 5, // shader locations 0-2 are taken up by Position, Normal and UV attributes
                 },
+                // tint color and alpha
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: (3 * 4 + 4 + 4 * 4),
+                    shader_location: 6,
+                },
+                // highlight towards white
+                VertexAttribute {
+                    format: VertexFormat::Float32,
+                    offset: (3 * 4 + 4 + 4 * 4 + 4 * 4),
+                    shader_location: 7,
+                },
             ],
         });
         descriptor.fragment.as_mut().unwrap().shader = self.shader.clone();
@@ -329,20 +525,35 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetTextureBindGroup<I> {
 pub struct DrawMesh2dInstanced;
 
 impl<P: PhaseItem> RenderCommand<P> for DrawMesh2dInstanced {
-    type Param = (SRes<RenderAssets<Mesh>>, SRes<RenderMesh2dInstances>);
-    type ViewQuery = ();
-    type ItemQuery = Read<InstanceBuffer>;
+    type Param = (
+        SRes<RenderAssets<Mesh>>,
+        SRes<RenderMesh2dInstances>,
+        SRes<InstanceBuffers>,
+        SRes<ViewInstanceBuffers>,
+    );
+    type ViewQuery = Entity;
+    type ItemQuery = ();
 
     #[inline]
     fn render<'w>(
         item: &P,
-        _view: (),
-        instance_buffer: Option<&'w InstanceBuffer>,
-        (meshes, render_mesh2d_instances): SystemParamItem<'w, '_, Self::Param>,
+        view: Entity,
+        _item_query: Option<()>,
+        (meshes, render_mesh2d_instances, instance_buffers, view_buffers): SystemParamItem<
+            'w,
+            '_,
+            Self::Param,
+        >,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         let meshes = meshes.into_inner();
         let render_mesh2d_instances = render_mesh2d_instances.into_inner();
+        // the culled instances of the view or all of them
+        let instance_buffer = view_buffers
+            .into_inner()
+            .0
+            .get(&view)
+            .or_else(|| instance_buffers.into_inner().0.get(&item.entity()));
 
         let Some(RenderMesh2dInstance { mesh_asset_id, .. }) =
             render_mesh2d_instances.get(&item.entity())
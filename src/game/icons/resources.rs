@@ -19,6 +19,8 @@ impl Default for UpdateTimer {
 pub struct IconSheetResource {
     pub handle: Handle<IconSheetAsset>,
     pub texture_array: Option<Handle<Image>>,
+    /// Size of each layer of the texture array, smaller sheets are padded
+    pub texture_array_size: Vec2,
 }
 
 #[derive(Resource)]
@@ -45,7 +45,7 @@ fn get_separation_force(
     position: &Vec2,
     _rotation: &f32,
     velocity: &Vec2,
-    nearest: &[(&Entity, &Vec2, &Vec2)],
+    nearest: &[(&Entity, Vec2, &Vec2)],
     separation_distance: f32,
     max_speed: f32,
     max_force: f32,
@@ -57,7 +57,7 @@ fn get_separation_force(
         if **other_entity == entity {
             continue;
         }
-        let mut diff = *position - **other_position;
+        let mut diff = *position - *other_position;
         let distance = diff.length();
         if distance < separation_distance {
             diff = diff.normalize();
@@ -89,7 +89,7 @@ fn get_alignment_force(
     position: &Vec2,
     _rotation: &f32,
     velocity: &Vec2,
-    nearest: &[(&Entity, &Vec2, &Vec2)],
+    nearest: &[(&Entity, Vec2, &Vec2)],
     alignment_distance: f32,
     max_speed: f32,
     max_force: f32,
@@ -101,7 +101,7 @@ fn get_alignment_force(
         if **other_entity == entity {
             continue;
         }
-        let distance = (*position - **other_position).length();
+        let distance = (*position - *other_position).length();
 
         if distance < alignment_distance {
             average_velocity += **other_velocity;
@@ -150,7 +150,7 @@ fn get_cohesion_force(
     position: &Vec2,
     _rotation: &f32,
     velocity: &Vec2,
-    nearest: &[(&Entity, &Vec2, &Vec2)],
+    nearest: &[(&Entity, Vec2, &Vec2)],
     cohesion_distance: f32,
     max_speed: f32,
     max_force: f32,
@@ -159,9 +159,9 @@ fn get_cohesion_force(
     let mut count = 0;
 
     for (_, other_position, _) in nearest {
-        let distance = (*position - **other_position).length();
+        let distance = (*position - *other_position).length();
         if distance < cohesion_distance {
-            average_position += **other_position;
+            average_position += *other_position;
             count += 1;
         }
     }
@@ -250,6 +250,9 @@ fn get_icon_velocity(
         max_force = settings.seek_max_force;
     }
 
+    // the player might be closer across the world edge
+    let target_position = &(*position + boundaries.delta(*position, *target_position));
+
     let nearest = spatial_index
         .simple_query(*position, settings.collision_distance)
         .collect::<Vec<_>>();
@@ -323,29 +326,41 @@ fn get_icon_velocity(
         acceleration += force * settings.seek_weight;
     }
 
-    // Boundary avoidance
-    if position.x < boundaries.bounds_min.x {
-        acceleration.x = settings.max_force;
-    }
-    if position.x > boundaries.bounds_max.x {
-        acceleration.x = -settings.max_force;
-    }
-    if position.y < boundaries.bounds_min.y {
-        acceleration.y = settings.max_force;
-    }
-    if position.y > boundaries.bounds_max.y {
-        acceleration.y = -settings.max_force;
+    // Boundary avoidance, icons leave through the edges of a wrapping world
+    if !boundaries.wrap {
+        if position.x < boundaries.bounds_min.x {
+            acceleration.x = settings.max_force;
+        }
+        if position.x > boundaries.bounds_max.x {
+            acceleration.x = -settings.max_force;
+        }
+        if position.y < boundaries.bounds_min.y {
+            acceleration.y = settings.max_force;
+        }
+        if position.y > boundaries.bounds_max.y {
+            acceleration.y = -settings.max_force;
+        }
     }
 
     // Inside the main logic
-    if let Some(force) = calculate_avoidance_force(
-        *position,
-        boundaries.dropzone_min,
-        boundaries.dropzone_max,
-        settings.avoidance_force_dropzone,
-        settings.avoidance_distance_dropzone,
-    ) {
-        acceleration = force;
+    for dropzone in boundaries.dropzones.iter() {
+        if let Some(force) = calculate_avoidance_force(
+            *position,
+            dropzone.min,
+            dropzone.max,
+            settings.avoidance_force_dropzone,
+            settings.avoidance_distance_dropzone,
+        ) {
+            acceleration = force;
+        }
+    }
+
+    // steer around obstacles
+    for obstacle in boundaries.obstacles.iter() {
+        let (normal, distance) = obstacle.signed_distance(*position);
+        if distance < settings.avoidance_distance_obstacle {
+            acceleration = normal * settings.avoidance_force_obstacle;
+        }
     }
 
     // // Check against the inner drop zone boundaries
@@ -24,11 +24,13 @@ pub struct SpatialIndex {
     pub grid_y: usize,
     pub len: usize,
     pub cell_size: f32,
+    /// Queries reach across the edges to the opposite side
+    pub wrap: bool,
     pub entities: Vec<EntityHashSet>,
     pub by_entity: EntityHashMap<(usize, Vec2, Vec2)>,
 }
 impl SpatialIndex {
-    pub fn new(min: Vec2, max: Vec2, cell_size: f32) -> Self {
+    pub fn new(min: Vec2, max: Vec2, cell_size: f32, wrap: bool) -> Self {
         let size = max - min;
         let grid_x = (size.x / cell_size).ceil() as usize;
         let grid_y = (size.y / cell_size).ceil() as usize;
@@ -40,6 +42,7 @@ impl SpatialIndex {
             grid_y,
             len,
             cell_size,
+            wrap,
             entities: Vec::from_iter((0..len).map(|_| EntityHashSet::default())),
             by_entity: EntityHashMap::default(),
         }
@@ -60,7 +63,7 @@ impl SpatialIndex {
         }
 
         let index = self.pos_to_index(position);
-        if index > 0 && index < self.len {
+        if position.cmpge(self.min).all() && index < self.len {
             self.entities[index].insert(entity);
             self.by_entity.insert(entity, (index, position, velocity));
         } else {
@@ -68,28 +71,75 @@ impl SpatialIndex {
         }
     }
 
-    pub fn simple_query(
-        &self,
-        position: Vec2,
-        distance: f32,
-    ) -> impl Iterator<Item = (&Entity, &Vec2, &Vec2)> + '_ {
+    /// Indices of the cells within distance of the position
+    fn cells_around(&self, position: Vec2, distance: f32) -> impl Iterator<Item = usize> + '_ {
         let grid_distance = (distance / self.cell_size).ceil() as i16;
 
         let x = ((position.x - self.min.x) / self.cell_size) as i16;
         let y = ((position.y - self.min.y) / self.cell_size) as i16;
 
-        offset_iter(x, y, grid_distance)
-            .flat_map(|(x, y)| {
-                let index = self.grid_x as i16 * y + x;
-                // println!("x:{} y:{} index={}", x, y, index);
+        let (grid_x, grid_y) = (self.grid_x as i16, self.grid_y as i16);
+        offset_iter(x, y, grid_distance).map(move |(x, y)| {
+            if self.wrap {
+                (grid_x * y.rem_euclid(grid_y) + x.rem_euclid(grid_x)) as usize
+            } else {
+                (grid_x * y + x) as usize
+            }
+        })
+    }
+
+    /// Entities in the cells that intersect the rectangle, the rectangle is
+    /// clipped to the index and does not wrap
+    pub fn query_rect(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = &Entity> + '_ {
+        let min_cell = ((min - self.min) / self.cell_size).floor().max(Vec2::ZERO);
+        let max_cell = ((max - self.min) / self.cell_size)
+            .floor()
+            .min(Vec2::new(self.grid_x as f32, self.grid_y as f32) - 1.0);
+        let (x_range, y_range) = (
+            min_cell.x as i32..=max_cell.x as i32,
+            min_cell.y as i32..=max_cell.y as i32,
+        );
+        let grid_x = self.grid_x;
+        y_range
+            .flat_map(move |y| {
+                x_range
+                    .clone()
+                    .map(move |x| grid_x * y as usize + x as usize)
+            })
+            .flat_map(|index| self.entities[index].iter())
+    }
+
+    /// Position of `other` as seen from `position`, moved across the edges
+    /// to the nearest side if the index wraps
+    fn relative_position(&self, position: Vec2, other: Vec2) -> Vec2 {
+        if !self.wrap {
+            return other;
+        }
+        let size = self.max - self.min;
+        let offset = other - position;
+        position + offset - size * (offset / size).round()
+    }
+
+    /// Nearby entities, positions are relative to the queried position
+    pub fn simple_query(
+        &self,
+        position: Vec2,
+        distance: f32,
+    ) -> impl Iterator<Item = (&Entity, Vec2, &Vec2)> + '_ {
+        self.cells_around(position, distance)
+            .flat_map(|index| {
                 self.entities
-                    .get(index as usize)
+                    .get(index)
                     .into_iter()
                     .flat_map(|entities| entities.iter())
             })
             .map(move |entity| {
                 let (_, other_position, other_velocity) = self.by_entity.get(entity).unwrap();
-                (entity, other_position, other_velocity)
+                (
+                    entity,
+                    self.relative_position(position, *other_position),
+                    other_velocity,
+                )
             })
     }
 
@@ -98,22 +148,16 @@ impl SpatialIndex {
         position: Vec2,
         distance: f32,
     ) -> impl Iterator<Item = SpatialQueryResult> + '_ {
-        let grid_distance = (distance / self.cell_size).ceil() as i16;
-
-        let x = ((position.x - self.min.x) / self.cell_size) as i16;
-        let y = ((position.y - self.min.y) / self.cell_size) as i16;
-
-        offset_iter(x, y, grid_distance)
-            .flat_map(|(x, y)| {
-                let index = self.grid_x as i16 * y + x;
+        self.cells_around(position, distance)
+            .flat_map(|index| {
                 self.entities
-                    .get(index as usize)
+                    .get(index)
                     .into_iter()
                     .flat_map(|entities| entities.iter())
             })
             .flat_map(move |entity| {
                 let (_, other_position, other_velocity) = self.by_entity.get(entity).unwrap();
-                let other_position = *other_position;
+                let other_position = self.relative_position(position, *other_position);
                 let distance_to_other = (position - other_position).length();
                 if distance_to_other <= distance {
                     Some(SpatialQueryResult {
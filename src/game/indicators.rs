@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use bevy::utils::HashSet;
+use bevy_prototype_lyon::prelude::*;
+
+use super::camera::{CameraTag, UiCameraTag, CAMERA_LAYER_UI};
+use super::hud::FontResource;
+use super::icons::{IconFollowers, IconPlayerController, IconTransform};
+use super::states::GameState;
+use super::theme::{ThemeColor, ThemeResource};
+use super::world::WorldBoundaryResource;
+
+/// Distance of the arrows from the edge of the viewport, in pixels
+const EDGE_MARGIN: f32 = 40.0;
+/// Arrows fade out over this distance in pixels as the target approaches the viewport
+const FADE_DISTANCE: f32 = 240.0;
+const ARROW_SIZE: f32 = 18.0;
+/// Distance of the label from the arrow, toward the center of the screen
+const LABEL_OFFSET: f32 = 34.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum IndicatorTarget {
+    Dropzone(usize),
+    Follower(Entity),
+}
+
+impl IndicatorTarget {
+    fn color(&self, theme: &ThemeResource) -> Color {
+        match self {
+            IndicatorTarget::Dropzone(_) => theme.color(ThemeColor::Accent),
+            IndicatorTarget::Follower(_) => theme.color(ThemeColor::Projectile),
+        }
+    }
+}
+
+/// Edge of screen indicator, parent of the arrow and the distance label
+#[derive(Component, Debug)]
+struct OffscreenIndicator(IndicatorTarget);
+
+#[derive(Component, Debug)]
+struct IndicatorArrowTag;
+
+#[derive(Component, Debug)]
+struct IndicatorLabelTag;
+
+pub struct IndicatorsPlugin;
+
+impl Plugin for IndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (sync_indicators_system, update_indicators_system)
+                .chain()
+                .run_if(in_state(GameState::GameRunning)),
+        );
+        app.add_systems(OnEnter(GameState::GamePaused), hide_indicators_system);
+        app.add_systems(OnEnter(GameState::MainMenu), despawn_indicators_system);
+        app.add_systems(OnEnter(GameState::GameOver), despawn_indicators_system);
+        app.add_systems(OnEnter(GameState::LevelEditor), despawn_indicators_system);
+    }
+}
+
+fn spawn_indicator(
+    commands: &mut Commands,
+    font: &FontResource,
+    theme: &ThemeResource,
+    target: IndicatorTarget,
+) {
+    // arrow pointing along +x, rotated toward the target
+    let arrow = shapes::Polygon {
+        points: vec![
+            Vec2::new(ARROW_SIZE, 0.0),
+            Vec2::new(-ARROW_SIZE / 2.0, ARROW_SIZE * 0.75),
+            Vec2::new(-ARROW_SIZE / 2.0, -ARROW_SIZE * 0.75),
+        ],
+        closed: true,
+    };
+
+    commands
+        .spawn((
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            OffscreenIndicator(target),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&arrow),
+                    ..Default::default()
+                },
+                Fill::color(target.color(theme)),
+                RenderLayers::layer(CAMERA_LAYER_UI),
+                NoFrustumCulling,
+                IndicatorArrowTag,
+            ));
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font.text2.clone(),
+                            font_size: 18.0,
+                            color: target.color(theme),
+                        },
+                    ),
+                    ..Default::default()
+                },
+                RenderLayers::layer(CAMERA_LAYER_UI),
+                NoFrustumCulling,
+                IndicatorLabelTag,
+            ));
+        });
+}
+
+/// Keep one indicator per target, followers come and go
+fn sync_indicators_system(
+    mut commands: Commands,
+    font: Res<FontResource>,
+    theme: Res<ThemeResource>,
+    followers: Res<IconFollowers>,
+    boundaries: Res<WorldBoundaryResource>,
+    indicators: Query<(Entity, &OffscreenIndicator)>,
+) {
+    let mut targets: HashSet<IndicatorTarget> = followers
+        .followers
+        .iter()
+        .map(|entity| IndicatorTarget::Follower(*entity))
+        .collect();
+    targets.extend((0..boundaries.dropzones.len()).map(IndicatorTarget::Dropzone));
+
+    for (entity, OffscreenIndicator(target)) in indicators.iter() {
+        if !targets.remove(target) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for target in targets {
+        spawn_indicator(&mut commands, &font, &theme, target);
+    }
+}
+
+fn format_distance(distance: f32) -> String {
+    if distance >= 1000.0 {
+        format!("{:.1}k", distance / 1000.0)
+    } else {
+        format!("{:.0}", distance)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn update_indicators_system(
+    camera: Query<(&Camera, &GlobalTransform), With<CameraTag>>,
+    ui_camera: Query<&OrthographicProjection, With<UiCameraTag>>,
+    window: Query<&Window>,
+    player_icon: Query<&IconTransform, With<IconPlayerController>>,
+    icons: Query<&IconTransform>,
+    boundaries: Res<WorldBoundaryResource>,
+    theme: Res<ThemeResource>,
+    mut indicators: Query<(
+        &OffscreenIndicator,
+        &Children,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    mut arrows: Query<
+        (&mut Transform, &mut Fill),
+        (With<IndicatorArrowTag>, Without<OffscreenIndicator>),
+    >,
+    mut labels: Query<
+        (&mut Transform, &mut Text),
+        (
+            With<IndicatorLabelTag>,
+            Without<OffscreenIndicator>,
+            Without<IndicatorArrowTag>,
+        ),
+    >,
+) {
+    let (Ok((camera, camera_transform)), Ok(projection), Ok(window), Ok(player)) = (
+        camera.get_single(),
+        ui_camera.get_single(),
+        window.get_single(),
+        player_icon.get_single(),
+    ) else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_rect() else {
+        return;
+    };
+    let half_size = viewport.half_size();
+    let inner_half_size = (half_size - Vec2::splat(EDGE_MARGIN)).max(Vec2::ONE);
+
+    // window coordinates to the world space of the ui camera
+    let scale = projection.scale;
+    let to_ui = |position: Vec2| {
+        Vec2::new(
+            position.x - window.width() / 2.0,
+            window.height() / 2.0 - position.y,
+        ) * scale
+    };
+
+    for (OffscreenIndicator(target), children, mut transform, mut visibility) in
+        indicators.iter_mut()
+    {
+        let world_position = match target {
+            // nothing to point at while already inside of it
+            IndicatorTarget::Dropzone(_) if boundaries.in_dropzone(player.position) => None,
+            IndicatorTarget::Dropzone(index) => boundaries
+                .dropzones
+                .get(*index)
+                .map(|dropzone| dropzone.center()),
+            IndicatorTarget::Follower(entity) => {
+                icons.get(*entity).ok().map(|transform| transform.position)
+            }
+        };
+        // point the short way across the edges of a wrapping world
+        let world_position = world_position
+            .map(|position| player.position + boundaries.delta(player.position, position));
+        let Some((world_position, viewport_position)) = world_position.and_then(|position| {
+            camera
+                .world_to_viewport(camera_transform, position.extend(0.0))
+                .map(|viewport_position| (position, viewport_position))
+        }) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // viewport is y down, flip to have y up like the ui camera
+        let offset = viewport_position - half_size;
+        let offset = Vec2::new(offset.x, -offset.y);
+        let fit = |half_size: Vec2| {
+            (half_size.x / offset.x.abs().max(f32::EPSILON))
+                .min(half_size.y / offset.y.abs().max(f32::EPSILON))
+        };
+
+        // fade out as the target gets close to the edge of the viewport
+        let outside = offset.length() * (1.0 - fit(half_size)).max(0.0);
+        let alpha = (outside / FADE_DISTANCE).min(1.0);
+        if alpha <= 0.0 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        let direction = offset.normalize_or_zero();
+        let edge = offset * fit(inner_half_size).min(1.0);
+        let center = to_ui(viewport.center());
+
+        *visibility = Visibility::Visible;
+        transform.translation = (center + edge * scale).extend(0.0);
+        transform.scale = Vec3::splat(scale);
+
+        for child in children.iter() {
+            if let Ok((mut arrow_transform, mut fill)) = arrows.get_mut(*child) {
+                arrow_transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+                fill.color = target.color(&theme).with_a(alpha);
+            }
+            if let Ok((mut label_transform, mut text)) = labels.get_mut(*child) {
+                label_transform.translation = (-direction * LABEL_OFFSET).extend(1.0);
+                text.sections[0].value = format_distance(player.position.distance(world_position));
+                text.sections[0].style.color = target.color(&theme).with_a(alpha);
+            }
+        }
+    }
+}
+
+/// The pause menu is drawn below the indicators, they are shown again once the game resumes
+fn hide_indicators_system(mut indicators: Query<&mut Visibility, With<OffscreenIndicator>>) {
+    for mut visibility in indicators.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn despawn_indicators_system(
+    mut commands: Commands,
+    indicators: Query<Entity, With<OffscreenIndicator>>,
+) {
+    for entity in indicators.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
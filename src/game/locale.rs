@@ -0,0 +1,348 @@
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use super::assets::locales::LocaleAsset;
+use super::controls::InputAction;
+use super::hud::FontResource;
+use super::settings::{ColorblindPalette, ControlScheme, FollowerMarker, RetroFilter};
+
+/// Keys of the translated texts, every text of the HUD and the menus is one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextKey {
+    GameTitle,
+    Pause,
+    CreditsGame,
+    /// Music, sound effects, fonts and the engine, one per line
+    Credits,
+    Instructions,
+    ControlsHeading,
+    ZoomControl,
+    ResumeGame,
+    StartGame,
+    Level,
+    Icons,
+    Controls,
+    Graphics,
+    Accessibility,
+    Language,
+    MusicOn,
+    MusicOff,
+    SoundOn,
+    SoundOff,
+    ScreenShakeOn,
+    ScreenShakeOff,
+    QuitGame,
+    BackToMainMenu,
+    Back,
+    On,
+    Off,
+    ControlsTitle,
+    RebindPrompt,
+    Scheme,
+    SchemeScreenRelative,
+    SchemeTank,
+    RotateCamera,
+    ResetControls,
+    ActionMoveForward,
+    ActionMoveBackward,
+    ActionStrafeLeft,
+    ActionStrafeRight,
+    ActionTurnLeft,
+    ActionTurnRight,
+    ActionShoot,
+    ActionPause,
+    ActionToggleDebug,
+    GraphicsTitle,
+    Theme,
+    Bloom,
+    DamageAberration,
+    Retro,
+    RetroScanlines,
+    RetroCrt,
+    PixelatedIcons,
+    AccessibilityTitle,
+    Palette,
+    PaletteRedGreen,
+    PaletteBlueYellow,
+    Followers,
+    MarkerRing,
+    MarkerDashedRing,
+    MarkerBadge,
+    ReducedMotion,
+    TextSize,
+    GameSpeed,
+    LevelsTitle,
+    IconsTitle,
+    YouWin,
+    GameOver,
+    WinMessage,
+    ThankYou,
+    Score,
+    Fps,
+    /// Popup when an icon was hit, with the name of the icon
+    NowFollowing,
+}
+
+impl TextKey {
+    /// Built-in English text, `{}` are replaced by the arguments in order
+    fn english(&self) -> &'static str {
+        match self {
+            TextKey::GameTitle => "ICON WARS!",
+            TextKey::Pause => "PAUSE",
+            TextKey::CreditsGame => "Game by @mattzq for Bevy Jam #4",
+            TextKey::Credits => {
+                "Music by Brylie Christopher Oxley (CC-BY-4.0)
+SFX by rubberduck (CC0)
+Gasoek One by Jiashuo Zhang and JAMO (OFL)
+DM Sans by Colophon Foundry, Jonny Pinhorn and Indian Type Foundry (OFL)
+DejaVu Sans by the DejaVu fonts team (Bitstream Vera License)
+
+and Bevy (https://bevyengine.org/)"
+            }
+            TextKey::Instructions => {
+                "Instructions:
+Shoot icons to make them follow you.
+You take damage if they touch you.
+Bring them to a dropzone (the lighter areas) to score points.
+You make more points the more followers you bring at once.
+You take more damage the more followers you have."
+            }
+            TextKey::ControlsHeading => "Controls:",
+            TextKey::ZoomControl => "Mouse Wheel - Zoom",
+            TextKey::ResumeGame => "Resume Game",
+            TextKey::StartGame => "Start Game",
+            TextKey::Level => "Level: {}",
+            TextKey::Icons => "Icons: {}",
+            TextKey::Controls => "Controls",
+            TextKey::Graphics => "Graphics",
+            TextKey::Accessibility => "Accessibility",
+            TextKey::Language => "Language: {}",
+            TextKey::MusicOn => "Music On",
+            TextKey::MusicOff => "Music Off",
+            TextKey::SoundOn => "Sound On",
+            TextKey::SoundOff => "Sound Off",
+            TextKey::ScreenShakeOn => "Screen Shake On",
+            TextKey::ScreenShakeOff => "Screen Shake Off",
+            TextKey::QuitGame => "Quit Game",
+            TextKey::BackToMainMenu => "Back to Main Menu",
+            TextKey::Back => "Back",
+            TextKey::On => "On",
+            TextKey::Off => "Off",
+            TextKey::ControlsTitle => "CONTROLS",
+            TextKey::RebindPrompt => "Press a key... (Esc cancels)",
+            TextKey::Scheme => "Scheme: {}",
+            TextKey::SchemeScreenRelative => "Screen Relative",
+            TextKey::SchemeTank => "Tank",
+            TextKey::RotateCamera => "Rotate Camera",
+            TextKey::ResetControls => "Reset to Defaults",
+            TextKey::ActionMoveForward => "Move Forward",
+            TextKey::ActionMoveBackward => "Move Backward",
+            TextKey::ActionStrafeLeft => "Strafe Left",
+            TextKey::ActionStrafeRight => "Strafe Right",
+            TextKey::ActionTurnLeft => "Turn Left",
+            TextKey::ActionTurnRight => "Turn Right",
+            TextKey::ActionShoot => "Shoot",
+            TextKey::ActionPause => "Pause Menu",
+            TextKey::ActionToggleDebug => "Development Settings",
+            TextKey::GraphicsTitle => "GRAPHICS",
+            TextKey::Theme => "Theme: {}",
+            TextKey::Bloom => "Bloom",
+            TextKey::DamageAberration => "Damage Aberration",
+            TextKey::Retro => "Retro: {}",
+            TextKey::RetroScanlines => "Scanlines",
+            TextKey::RetroCrt => "CRT",
+            TextKey::PixelatedIcons => "Pixelated Icons",
+            TextKey::AccessibilityTitle => "ACCESSIBILITY",
+            TextKey::Palette => "Palette: {}",
+            TextKey::PaletteRedGreen => "Red-Green",
+            TextKey::PaletteBlueYellow => "Blue-Yellow",
+            TextKey::Followers => "Followers: {}",
+            TextKey::MarkerRing => "Ring",
+            TextKey::MarkerDashedRing => "Dashed Ring",
+            TextKey::MarkerBadge => "Badge",
+            TextKey::ReducedMotion => "Reduced Motion",
+            TextKey::TextSize => "Text Size: {}%",
+            TextKey::GameSpeed => "Game Speed: {}%",
+            TextKey::LevelsTitle => "LEVELS",
+            TextKey::IconsTitle => "ICONS",
+            TextKey::YouWin => "YOU WIN!",
+            TextKey::GameOver => "GAME OVER",
+            TextKey::WinMessage => {
+                "Congratulations! You collected ALL the icons!
+That's a LOT of icons!
+Thank you for playing! <3
+Score: {}"
+            }
+            TextKey::ThankYou => "Thank you for playing! <3\nScore: {}",
+            TextKey::Score => "Score: {}",
+            TextKey::Fps => "{} FPS",
+            TextKey::NowFollowing => "{} IS NOW FOLLOWING YOU",
+        }
+    }
+
+    /// Label of the action with the given control scheme, left and right turn with the tank scheme
+    pub fn action(action: InputAction, scheme: ControlScheme) -> Self {
+        match (scheme, action) {
+            (ControlScheme::Tank, InputAction::MoveLeft) => TextKey::ActionTurnLeft,
+            (ControlScheme::Tank, InputAction::MoveRight) => TextKey::ActionTurnRight,
+            (_, InputAction::MoveUp) => TextKey::ActionMoveForward,
+            (_, InputAction::MoveDown) => TextKey::ActionMoveBackward,
+            (_, InputAction::MoveLeft) => TextKey::ActionStrafeLeft,
+            (_, InputAction::MoveRight) => TextKey::ActionStrafeRight,
+            (_, InputAction::Shoot) => TextKey::ActionShoot,
+            (_, InputAction::Pause) => TextKey::ActionPause,
+            (_, InputAction::ToggleDebug) => TextKey::ActionToggleDebug,
+        }
+    }
+
+    pub fn scheme(scheme: ControlScheme) -> Self {
+        match scheme {
+            ControlScheme::ScreenRelative => TextKey::SchemeScreenRelative,
+            ControlScheme::Tank => TextKey::SchemeTank,
+        }
+    }
+
+    pub fn retro_filter(filter: RetroFilter) -> Self {
+        match filter {
+            RetroFilter::Off => TextKey::Off,
+            RetroFilter::Scanlines => TextKey::RetroScanlines,
+            RetroFilter::Crt => TextKey::RetroCrt,
+        }
+    }
+
+    pub fn palette(palette: ColorblindPalette) -> Self {
+        match palette {
+            ColorblindPalette::Off => TextKey::Off,
+            ColorblindPalette::RedGreen => TextKey::PaletteRedGreen,
+            ColorblindPalette::BlueYellow => TextKey::PaletteBlueYellow,
+        }
+    }
+
+    pub fn follower_marker(marker: FollowerMarker) -> Self {
+        match marker {
+            FollowerMarker::Ring => TextKey::MarkerRing,
+            FollowerMarker::DashedRing => TextKey::MarkerDashedRing,
+            FollowerMarker::Badge => TextKey::MarkerBadge,
+        }
+    }
+}
+
+/// Fonts of a locale, missing ones are replaced by the default fonts
+#[derive(Debug, Clone, Default)]
+pub struct LocaleFonts {
+    pub title: Option<Handle<Font>>,
+    pub text: Option<Handle<Font>>,
+    pub text2: Option<Handle<Font>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub name: String,
+    texts: HashMap<TextKey, String>,
+    pub fonts: LocaleFonts,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            name: "English".to_string(),
+            texts: HashMap::new(),
+            fonts: LocaleFonts::default(),
+        }
+    }
+}
+
+impl Locale {
+    pub fn new(name: String, texts: HashMap<TextKey, String>, fonts: LocaleFonts) -> Self {
+        Self { name, texts, fonts }
+    }
+
+    /// Texts missing from the locale are taken from the built-in English texts
+    pub fn text(&self, key: TextKey) -> &str {
+        self.texts
+            .get(&key)
+            .map(String::as_str)
+            .unwrap_or_else(|| key.english())
+    }
+
+    /// The text with each `{}` replaced by the next argument
+    pub fn format(&self, key: TextKey, args: &[&dyn Display]) -> String {
+        let mut parts = self.text(key).split("{}");
+        let mut result = parts.next().unwrap_or_default().to_string();
+        let mut args = args.iter();
+        for part in parts {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+            result.push_str(part);
+        }
+        result
+    }
+
+    pub fn on_off(&self, key: TextKey, value: bool) -> String {
+        let state = if value { TextKey::On } else { TextKey::Off };
+        format!("{}: {}", self.text(key), self.text(state))
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct LocaleResource {
+    pub handles: Vec<Handle<LocaleAsset>>,
+    /// The built-in English locale followed by the ones that loaded
+    locales: Vec<Locale>,
+    current: usize,
+    /// Fonts of the locales that do not bring their own
+    pub default_fonts: FontResource,
+}
+
+impl Default for LocaleResource {
+    fn default() -> Self {
+        Self {
+            handles: Vec::new(),
+            locales: vec![Locale::default()],
+            current: 0,
+            default_fonts: FontResource::default(),
+        }
+    }
+}
+
+impl LocaleResource {
+    pub fn current(&self) -> &Locale {
+        &self.locales[self.current]
+    }
+
+    /// Add locales after the built-in one, keeping the current locale
+    pub fn add_locales(&mut self, locales: Vec<Locale>) {
+        self.locales.truncate(1);
+        self.locales.extend(locales);
+        self.current = self.current.min(self.locales.len() - 1);
+    }
+
+    /// Switch to the next locale, wrapping around
+    pub fn cycle(&mut self) {
+        self.current = (self.current + 1) % self.locales.len();
+    }
+
+    /// Fonts of the current locale, completed with the default fonts
+    pub fn fonts(&self) -> FontResource {
+        let fonts = &self.current().fonts;
+        let defaults = &self.default_fonts;
+        FontResource {
+            title: fonts.title.clone().unwrap_or(defaults.title.clone()),
+            text: fonts.text.clone().unwrap_or(defaults.text.clone()),
+            text2: fonts.text2.clone().unwrap_or(defaults.text2.clone()),
+        }
+    }
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LocaleResource::default());
+    }
+}
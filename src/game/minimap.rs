@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy::render::view::RenderLayers;
+
+use super::camera::CAMERA_LAYER_UI;
+use super::icons::{IconTransform, IconType, Type};
+use super::settings::SettingsResource;
+use super::states::GameState;
+use super::theme::{ThemeColor, ThemeResource};
+use super::world::{Obstacle, WorldBoundaryResource};
+
+/// Texture width of the minimap, the height follows the aspect ratio of the world
+const MINIMAP_RESOLUTION: u32 = 160;
+
+/// The world shows through the background of the minimap
+const BACKGROUND_ALPHA: f32 = 0.87;
+
+#[derive(Component)]
+pub struct MinimapTag;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameRunning), spawn_minimap_system);
+        app.add_systems(OnEnter(GameState::MainMenu), despawn_minimap_system);
+        app.add_systems(OnEnter(GameState::GameOver), despawn_minimap_system);
+        app.add_systems(OnEnter(GameState::LevelEditor), despawn_minimap_system);
+        app.add_systems(
+            Update,
+            (update_minimap_style_system, update_minimap_system)
+                .run_if(in_state(GameState::GameRunning).or_else(in_state(GameState::GamePaused))),
+        );
+    }
+}
+
+/// Minimap size in pixels, fit to the aspect ratio of the world
+fn minimap_size(boundaries: &WorldBoundaryResource, width: f32) -> Vec2 {
+    let world_size = boundaries.size();
+    Vec2::new(width, width * world_size.y / world_size.x)
+}
+
+fn spawn_minimap_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    boundaries: Res<WorldBoundaryResource>,
+    settings: Res<SettingsResource>,
+    minimap: Query<Entity, With<MinimapTag>>,
+) {
+    // still around when resuming from the pause menu
+    if !minimap.is_empty() {
+        return;
+    }
+
+    let texture_size = minimap_size(&boundaries, MINIMAP_RESOLUTION as f32).max(Vec2::ONE);
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: texture_size.x as u32,
+            height: texture_size.y as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        // drawn over with the theme colors every frame
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+
+    let size = minimap_size(&boundaries, settings.minimap_size);
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(32.0),
+                right: Val::Px(32.0),
+                width: Val::Px(size.x),
+                height: Val::Px(size.y),
+                ..Default::default()
+            },
+            image: UiImage::new(images.add(image)),
+            background_color: Color::rgba(1.0, 1.0, 1.0, settings.minimap_opacity).into(),
+            visibility: if settings.minimap {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..Default::default()
+        },
+        RenderLayers::layer(CAMERA_LAYER_UI),
+        MinimapTag,
+    ));
+}
+
+fn despawn_minimap_system(mut commands: Commands, minimap: Query<Entity, With<MinimapTag>>) {
+    for entity in minimap.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_minimap_style_system(
+    mut minimap: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<MinimapTag>>,
+    boundaries: Res<WorldBoundaryResource>,
+    settings: Res<SettingsResource>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let size = minimap_size(&boundaries, settings.minimap_size);
+    for (mut style, mut background_color, mut visibility) in minimap.iter_mut() {
+        style.width = Val::Px(size.x);
+        style.height = Val::Px(size.y);
+        background_color.0 = Color::rgba(1.0, 1.0, 1.0, settings.minimap_opacity);
+        *visibility = if settings.minimap {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Pixel buffer of the minimap texture with world to texel mapping
+struct MinimapCanvas<'a> {
+    data: &'a mut [u8],
+    width: i32,
+    height: i32,
+    bounds_min: Vec2,
+    bounds_size: Vec2,
+}
+
+impl<'a> MinimapCanvas<'a> {
+    fn to_texel(&self, position: Vec2) -> IVec2 {
+        let uv = (position - self.bounds_min) / self.bounds_size;
+        // world is y up, the texture is y down
+        IVec2::new(
+            (uv.x * self.width as f32) as i32,
+            ((1.0 - uv.y) * self.height as f32) as i32,
+        )
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: [u8; 4]) {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            let index = ((y * self.width + x) * 4) as usize;
+            self.data[index..index + 4].copy_from_slice(&color);
+        }
+    }
+
+    fn fill(&mut self, color: [u8; 4]) {
+        for pixel in self.data.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    /// Square dot of `size` texels centered on the world position
+    fn dot(&mut self, position: Vec2, size: i32, color: [u8; 4]) {
+        let texel = self.to_texel(position) - IVec2::splat(size / 2);
+        for y in texel.y..texel.y + size {
+            for x in texel.x..texel.x + size {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Fill all texels whose center lies within the obstacle
+    fn obstacle(&mut self, obstacle: &Obstacle, color: [u8; 4]) {
+        let (min, max) = obstacle.bounds();
+        let (a, b) = (self.to_texel(min), self.to_texel(max));
+        let texel_size = self.bounds_size / Vec2::new(self.width as f32, self.height as f32);
+        for y in a.y.min(b.y)..=a.y.max(b.y) {
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                let position = Vec2::new(
+                    self.bounds_min.x + (x as f32 + 0.5) * texel_size.x,
+                    self.bounds_min.y + self.bounds_size.y - (y as f32 + 0.5) * texel_size.y,
+                );
+                if obstacle.contains(position) {
+                    self.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn rect(&mut self, min: Vec2, max: Vec2, fill: [u8; 4], border: [u8; 4]) {
+        let (a, b) = (self.to_texel(min), self.to_texel(max));
+        let last = IVec2::new(self.width - 1, self.height - 1);
+        let (min, max) = (
+            a.min(b).clamp(IVec2::ZERO, last),
+            a.max(b).clamp(IVec2::ZERO, last),
+        );
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let is_border = x == min.x || x == max.x || y == min.y || y == max.y;
+                self.set(x, y, if is_border { border } else { fill });
+            }
+        }
+    }
+}
+
+fn update_minimap_system(
+    minimap: Query<(&UiImage, &InheritedVisibility), With<MinimapTag>>,
+    mut images: ResMut<Assets<Image>>,
+    icons: Query<(&IconTransform, &IconType)>,
+    boundaries: Res<WorldBoundaryResource>,
+    theme: Res<ThemeResource>,
+) {
+    let Ok((image, visibility)) = minimap.get_single() else {
+        return;
+    };
+    if !visibility.get() {
+        return;
+    }
+    let Some(image) = images.get_mut(&image.texture) else {
+        return;
+    };
+
+    let size = image.size();
+    let mut canvas = MinimapCanvas {
+        data: &mut image.data,
+        width: size.x as i32,
+        height: size.y as i32,
+        bounds_min: boundaries.bounds_min,
+        bounds_size: boundaries.size(),
+    };
+
+    let color = |color: ThemeColor| theme.color(color).as_rgba_u8();
+    let background = theme
+        .color(ThemeColor::Background)
+        .with_a(BACKGROUND_ALPHA)
+        .as_rgba_u8();
+    canvas.fill(background);
+    canvas.rect(
+        boundaries.bounds_min,
+        boundaries.bounds_max,
+        background,
+        color(ThemeColor::Grid),
+    );
+    for dropzone in boundaries.dropzones.iter() {
+        canvas.rect(
+            dropzone.min,
+            dropzone.max,
+            color(ThemeColor::Dropzone),
+            color(ThemeColor::Accent),
+        );
+    }
+
+    for obstacle in boundaries.obstacles.iter() {
+        canvas.obstacle(obstacle, color(ThemeColor::ObstacleBorder));
+    }
+
+    // free icons first, so followers and the player are drawn on top
+    for (icon_type, size, color) in [
+        (Type::Free, 1, color(ThemeColor::Text)),
+        (Type::Follower, 2, color(ThemeColor::Projectile)),
+        (Type::Player, 4, color(ThemeColor::Damage)),
+    ] {
+        for (transform, _) in icons.iter().filter(|(_, ty)| ty.0 == icon_type) {
+            canvas.dot(transform.position, size, color);
+        }
+    }
+}
@@ -1,15 +1,26 @@
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::prelude::*;
 
+mod accessibility;
 mod assets;
 mod audio;
+mod background;
 mod camera;
+mod controls;
 mod debug;
+mod editor;
 mod hud;
 mod icons;
+mod indicators;
+mod locale;
+mod minimap;
+mod particles;
+mod postprocess;
 mod render;
 mod settings;
 mod states;
+mod theme;
+mod touch;
 mod world;
 
 pub struct GamePlugin;
@@ -18,15 +29,30 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(settings::SettingsResource::default());
         app.init_state::<states::GameState>();
+        app.add_plugins((theme::ThemePlugin, locale::LocalePlugin));
         app.add_plugins((
             assets::GameAssetPlugin,
             world::WorldPlugin,
+            background::BackgroundPlugin,
             icons::IconPlugin,
             render::RenderPlugin,
             camera::CameraPlugin,
+            controls::ControlsPlugin,
+            touch::TouchControlsPlugin,
             hud::HudPlugin,
+            minimap::MinimapPlugin,
+            indicators::IndicatorsPlugin,
             audio::AudioPlugin,
         ));
-        app.add_plugins((debug::DebugPlugin, FrameTimeDiagnosticsPlugin));
+        app.add_plugins((
+            particles::ParticlePlugin,
+            postprocess::PostProcessPlugin,
+            accessibility::AccessibilityPlugin,
+        ));
+        app.add_plugins((
+            debug::DebugPlugin,
+            editor::LevelEditorPlugin,
+            FrameTimeDiagnosticsPlugin,
+        ));
     }
 }
@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+use bevy::render::batching::NoAutomaticBatching;
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use bevy::sprite::Mesh2dHandle;
+use bytemuck::{Pod, Zeroable};
+use rand::prelude::*;
+
+use super::camera::{CAMERA_LAYER, CAMERA_Z_VFX};
+use super::icons::events::{
+    IconCaptureEvent, PlayerDamageEvent, PlayerFollowEvent, ProjectileSpawnEvent,
+};
+use super::icons::{IconPlayerController, IconTransform};
+use super::settings::SettingsResource;
+use super::states::GameState;
+use super::theme::{Theme, ThemeColor, ThemeResource};
+use super::world::WorldBoundaryResource;
+
+mod renderer;
+
+/// Distance between the sparkles of a capture trail
+const TRAIL_SPACING: f32 = 48.0;
+const TRAIL_MAX_SPARKLES: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+    pub size: f32,
+    /// Linear rgba, the alpha fades out over the lifetime
+    pub color: Vec4,
+}
+
+/// Shape of a burst of particles
+pub struct ParticleBurst {
+    pub count: usize,
+    pub color: ThemeColor,
+    /// Angle around the direction in radians, TAU emits in all directions
+    pub spread: f32,
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    pub size: (f32, f32),
+    /// Brightness multiplier while bloom is enabled, above 1 glows
+    pub glow: f32,
+}
+
+const MUZZLE_FLASH: ParticleBurst = ParticleBurst {
+    count: 4,
+    color: ThemeColor::Projectile,
+    spread: 0.6,
+    speed: (150.0, 400.0),
+    lifetime: (0.08, 0.2),
+    size: (6.0, 14.0),
+    glow: 4.0,
+};
+
+const CONVERSION_BURST: ParticleBurst = ParticleBurst {
+    count: 24,
+    color: ThemeColor::FollowerTint,
+    spread: std::f32::consts::TAU,
+    speed: (60.0, 260.0),
+    lifetime: (0.3, 0.6),
+    size: (4.0, 10.0),
+    glow: 3.0,
+};
+
+const CAPTURE_SPARKLE: ParticleBurst = ParticleBurst {
+    count: 1,
+    color: ThemeColor::Capture,
+    spread: std::f32::consts::TAU,
+    speed: (10.0, 60.0),
+    lifetime: (0.4, 0.9),
+    size: (4.0, 9.0),
+    glow: 6.0,
+};
+
+const DAMAGE_DEBRIS: ParticleBurst = ParticleBurst {
+    count: 32,
+    color: ThemeColor::Damage,
+    spread: 2.5,
+    speed: (120.0, 480.0),
+    lifetime: (0.3, 0.8),
+    size: (3.0, 8.0),
+    glow: 2.0,
+};
+
+/// Record of one particle in the instance buffer, see `particles.wgsl`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct ParticleInstance {
+    /// x, y and size
+    pub transform: [f32; 3],
+    /// Linear rgba
+    pub color: [f32; 4],
+}
+
+impl From<&Particle> for ParticleInstance {
+    fn from(particle: &Particle) -> Self {
+        // fade out and shrink a little towards the end
+        let t = 1.0 - particle.age / particle.lifetime;
+        let size = particle.size * (0.5 + 0.5 * t);
+        Self {
+            transform: [particle.position.x, particle.position.y, size],
+            color: particle.color.xyz().extend(particle.color.w * t).to_array(),
+        }
+    }
+}
+
+/// All particles are rendered with this single entity, like the icons
+#[derive(Component, Debug, Default)]
+pub struct ParticleInstanceData {
+    pub particles: Vec<Particle>,
+    /// Records of the particles in the layout of the instance buffer, the
+    /// allocation is reused across frames
+    pub instances: Vec<ParticleInstance>,
+}
+
+impl ParticleInstanceData {
+    pub const INSTANCE_LEN: u64 = std::mem::size_of::<ParticleInstance>() as u64;
+
+    fn update_instances(&mut self) {
+        let Self {
+            particles,
+            instances,
+        } = self;
+        instances.clear();
+        instances.extend(particles.iter().map(ParticleInstance::from));
+    }
+
+    pub fn emit(
+        &mut self,
+        rng: &mut ThreadRng,
+        burst: &ParticleBurst,
+        position: Vec2,
+        direction: Vec2,
+        settings: &SettingsResource,
+        theme: &Theme,
+    ) {
+        let count = (burst.count as f32 * settings.particles_amount).round() as usize;
+        let count =
+            count.min((settings.particles_max as usize).saturating_sub(self.particles.len()));
+        let mut color = theme.linear(burst.color);
+        if settings.bloom {
+            color = (color.xyz() * burst.glow).extend(color.w);
+        }
+        let angle = direction.y.atan2(direction.x);
+
+        for _ in 0..count {
+            let angle = angle + rng.gen_range(-0.5..=0.5) * burst.spread;
+            let speed = rng.gen_range(burst.speed.0..=burst.speed.1);
+            self.particles.push(Particle {
+                position,
+                velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                age: 0.0,
+                lifetime: rng.gen_range(burst.lifetime.0..=burst.lifetime.1),
+                size: rng.gen_range(burst.size.0..=burst.size.1),
+                color,
+            });
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct ParticleRenderEntity;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(renderer::ParticleRendererPlugin);
+        app.add_systems(Startup, spawn_particle_render_entity);
+        app.add_systems(OnEnter(GameState::MainMenu), clear_particles_system);
+        app.add_systems(
+            Update,
+            (emit_particles_system, update_particles_system)
+                .chain()
+                .run_if(in_state(GameState::GameRunning)),
+        );
+    }
+}
+
+fn spawn_particle_render_entity(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    // unit quad, scaled by the size of each particle in the shader
+    let mesh = Mesh::from(Rectangle {
+        half_size: Vec2::splat(0.5),
+    });
+    commands.spawn((
+        ParticleRenderEntity,
+        Mesh2dHandle(meshes.add(mesh)),
+        SpatialBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, CAMERA_Z_VFX + 0.5)),
+            ..Default::default()
+        },
+        RenderLayers::layer(CAMERA_LAYER),
+        ParticleInstanceData::default(),
+        NoFrustumCulling,
+        NoAutomaticBatching,
+    ));
+}
+
+fn clear_particles_system(mut instance_data: Query<&mut ParticleInstanceData>) {
+    for mut instance_data in instance_data.iter_mut() {
+        instance_data.particles.clear();
+        instance_data.instances.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn emit_particles_system(
+    mut instance_data: Query<&mut ParticleInstanceData>,
+    icons: Query<&IconTransform>,
+    player: Query<&IconTransform, With<IconPlayerController>>,
+    mut projectile_spawn_events: EventReader<ProjectileSpawnEvent>,
+    mut follow_events: EventReader<PlayerFollowEvent>,
+    mut capture_events: EventReader<IconCaptureEvent>,
+    mut damage_events: EventReader<PlayerDamageEvent>,
+    boundaries: Res<WorldBoundaryResource>,
+    settings: Res<SettingsResource>,
+    theme: Res<ThemeResource>,
+) {
+    let Ok(mut instance_data) = instance_data.get_single_mut() else {
+        return;
+    };
+    // no bursts with reduced motion either
+    if !settings.particles || settings.reduced_motion {
+        projectile_spawn_events.clear();
+        follow_events.clear();
+        capture_events.clear();
+        damage_events.clear();
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let theme = theme.current();
+
+    for ProjectileSpawnEvent {
+        position,
+        direction,
+    } in projectile_spawn_events.read()
+    {
+        instance_data.emit(
+            &mut rng,
+            &MUZZLE_FLASH,
+            *position,
+            *direction,
+            &settings,
+            theme,
+        );
+    }
+
+    for PlayerFollowEvent { entity } in follow_events.read() {
+        if let Ok(transform) = icons.get(*entity) {
+            instance_data.emit(
+                &mut rng,
+                &CONVERSION_BURST,
+                transform.position,
+                Vec2::X,
+                &settings,
+                theme,
+            );
+        }
+    }
+
+    // a trail of sparkles from where the follower was to its place in the dropzone
+    for IconCaptureEvent { entity, from } in capture_events.read() {
+        let Ok(transform) = icons.get(*entity) else {
+            continue;
+        };
+        let offset = boundaries.delta(*from, transform.position);
+        let n_sparkles = ((offset.length() / TRAIL_SPACING) as usize).clamp(1, TRAIL_MAX_SPARKLES);
+        for i in 0..=n_sparkles {
+            let position =
+                boundaries.wrap_position(*from + offset * (i as f32 / n_sparkles as f32));
+            instance_data.emit(
+                &mut rng,
+                &CAPTURE_SPARKLE,
+                position,
+                Vec2::X,
+                &settings,
+                theme,
+            );
+        }
+    }
+
+    for PlayerDamageEvent { position, .. } in damage_events.read() {
+        let Ok(player) = player.get_single() else {
+            continue;
+        };
+        // debris flies away from the icon that hit the player
+        let direction = (player.position - *position).normalize_or_zero();
+        let direction = if direction == Vec2::ZERO {
+            Vec2::Y
+        } else {
+            direction
+        };
+        instance_data.emit(
+            &mut rng,
+            &DAMAGE_DEBRIS,
+            player.position,
+            direction,
+            &settings,
+            theme,
+        );
+    }
+}
+
+fn update_particles_system(mut instance_data: Query<&mut ParticleInstanceData>, time: Res<Time>) {
+    let Ok(mut instance_data) = instance_data.get_single_mut() else {
+        return;
+    };
+    let dt = time.delta_seconds();
+    // particles slow down over time
+    let drag = (-3.0 * dt).exp();
+    instance_data.particles.retain_mut(|particle| {
+        particle.age += dt;
+        particle.position += particle.velocity * dt;
+        particle.velocity *= drag;
+        particle.age < particle.lifetime
+    });
+    instance_data.update_instances();
+}
@@ -0,0 +1,283 @@
+use bevy::core_pipeline::core_2d::Transparent2d;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::lifetimeless::*;
+use bevy::ecs::system::SystemParamItem;
+use bevy::prelude::*;
+use bevy::render::mesh::{GpuBufferInfo, MeshVertexBufferLayout};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_phase::{
+    AddRenderCommand, DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult, RenderPhase,
+    SetItemPipeline, TrackedRenderPass,
+};
+use bevy::render::render_resource::*;
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::render::view::{ExtractedView, VisibleEntities};
+use bevy::render::{Extract, ExtractSchedule, Render, RenderApp, RenderSet};
+use bevy::sprite::{
+    Mesh2dPipeline, Mesh2dPipelineKey, RenderMesh2dInstance, RenderMesh2dInstances,
+    SetMesh2dBindGroup, SetMesh2dViewBindGroup,
+};
+use bevy::utils::FloatOrd;
+
+use super::{ParticleInstance, ParticleInstanceData};
+
+/// Particles of the render entities, the allocations are reused across frames
+/// since all particles move every frame anyway
+#[derive(Resource, Default)]
+pub struct ExtractedParticleInstances(EntityHashMap<Vec<ParticleInstance>>);
+
+pub struct ParticleInstanceBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+/// Instance buffers of the render entities, kept across frames, the buffers only grow
+#[derive(Resource, Default)]
+pub struct ParticleInstanceBuffers(EntityHashMap<ParticleInstanceBuffer>);
+
+pub struct ParticleRendererPlugin;
+
+impl Plugin for ParticleRendererPlugin {
+    fn build(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .add_render_command::<Transparent2d, DrawParticles>()
+            .init_resource::<SpecializedMeshPipelines<ParticlePipeline>>()
+            .init_resource::<ExtractedParticleInstances>()
+            .init_resource::<ParticleInstanceBuffers>()
+            .add_systems(ExtractSchedule, extract_particle_instances)
+            .add_systems(
+                Render,
+                (
+                    queue_particles.in_set(RenderSet::QueueMeshes),
+                    prepare_particle_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<ParticlePipeline>();
+    }
+}
+
+fn extract_particle_instances(
+    mut extracted: ResMut<ExtractedParticleInstances>,
+    query: Extract<Query<(Entity, &ParticleInstanceData)>>,
+) {
+    extracted.0.retain(|entity, _| query.get(*entity).is_ok());
+    for (entity, instance_data) in &query {
+        let instances = extracted.0.entry(entity).or_default();
+        instances.clear();
+        instances.extend_from_slice(&instance_data.instances);
+    }
+}
+
+/// Queue the particle entity with our pipeline, like the icons
+#[allow(clippy::too_many_arguments)]
+fn queue_particles(
+    transparent_draw_functions: Res<DrawFunctions<Transparent2d>>,
+    particle_pipeline: Res<ParticlePipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<ParticlePipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    meshes: Res<RenderAssets<Mesh>>,
+    render_mesh_instances: Res<RenderMesh2dInstances>,
+    mut views: Query<(
+        &VisibleEntities,
+        &mut RenderPhase<Transparent2d>,
+        &ExtractedView,
+    )>,
+    extracted: Res<ExtractedParticleInstances>,
+) {
+    let draw_particles = transparent_draw_functions.read().id::<DrawParticles>();
+
+    for (visible_entities, mut transparent_phase, view) in &mut views {
+        let view_key = Mesh2dPipelineKey::from_msaa_samples(msaa.samples())
+            | Mesh2dPipelineKey::from_hdr(view.hdr);
+
+        for visible_entity in &visible_entities.entities {
+            // nothing to draw without any particles
+            let Some(instances) = extracted.0.get(visible_entity) else {
+                continue;
+            };
+            if instances.is_empty() {
+                continue;
+            }
+            let Some(mesh_instance) = render_mesh_instances.get(visible_entity) else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+
+            let key =
+                view_key | Mesh2dPipelineKey::from_primitive_topology(mesh.primitive_topology);
+            let pipeline_id = pipelines
+                .specialize(&pipeline_cache, &particle_pipeline, key, &mesh.layout)
+                .unwrap();
+
+            transparent_phase.add(Transparent2d {
+                entity: *visible_entity,
+                draw_function: draw_particles,
+                pipeline: pipeline_id,
+                sort_key: FloatOrd(mesh_instance.transforms.transform.translation.z),
+                batch_range: 0..1,
+                dynamic_offset: None,
+            });
+        }
+    }
+}
+
+fn prepare_particle_buffers(
+    extracted: Res<ExtractedParticleInstances>,
+    mut instance_buffers: ResMut<ParticleInstanceBuffers>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    instance_buffers
+        .0
+        .retain(|entity, _| extracted.0.contains_key(entity));
+
+    for (entity, instances) in &extracted.0 {
+        if instances.is_empty() {
+            continue;
+        }
+        // only allocate when there are more particles than ever before
+        let size = instances.len() as u64 * ParticleInstanceData::INSTANCE_LEN;
+        if !matches!(instance_buffers.0.get(entity), Some(instance_buffer) if instance_buffer.buffer.size() >= size)
+        {
+            let buffer = render_device.create_buffer(&BufferDescriptor {
+                label: Some("particle instance data buffer"),
+                size: size.next_power_of_two(),
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            instance_buffers
+                .0
+                .insert(*entity, ParticleInstanceBuffer { buffer, length: 0 });
+        }
+        let instance_buffer = instance_buffers.0.get_mut(entity).unwrap();
+        instance_buffer.length = instances.len();
+        render_queue.write_buffer(&instance_buffer.buffer, 0, bytemuck::cast_slice(instances));
+    }
+}
+
+#[derive(Resource)]
+pub struct ParticlePipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: Mesh2dPipeline,
+}
+
+impl FromWorld for ParticlePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let shader = asset_server.load("particles.wgsl");
+
+        ParticlePipeline {
+            shader,
+            mesh_pipeline: Mesh2dPipeline::from_world(world),
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for ParticlePipeline {
+    type Key = Mesh2dPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayout,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+
+        descriptor
+            .vertex
+            .shader_defs
+            .push("MESH_BINDGROUP_1".into());
+
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: ParticleInstanceData::INSTANCE_LEN,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                // position x, y and size
+                VertexAttribute {
+                    format: VertexFormat::Float32x3,
+                    offset: 0,
+                    shader_location: 3, // shader locations 0-2 are taken up by Position, Normal and UV attributes
+                },
+                // linear rgba color
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: (3 * 4),
+                    shader_location: 4,
+                },
+            ],
+        });
+        descriptor.fragment.as_mut().unwrap().shader = self.shader.clone();
+
+        Ok(descriptor)
+    }
+}
+
+type DrawParticles = (
+    SetItemPipeline,
+    SetMesh2dViewBindGroup<0>,
+    SetMesh2dBindGroup<1>,
+    DrawParticlesInstanced,
+);
+
+pub struct DrawParticlesInstanced;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawParticlesInstanced {
+    type Param = (
+        SRes<RenderAssets<Mesh>>,
+        SRes<RenderMesh2dInstances>,
+        SRes<ParticleInstanceBuffers>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = ();
+
+    #[inline]
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        _item_query: Option<()>,
+        (meshes, render_mesh2d_instances, instance_buffers): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let meshes = meshes.into_inner();
+        let render_mesh2d_instances = render_mesh2d_instances.into_inner();
+        let instance_buffer = instance_buffers.into_inner().0.get(&item.entity());
+
+        let Some(RenderMesh2dInstance { mesh_asset_id, .. }) =
+            render_mesh2d_instances.get(&item.entity())
+        else {
+            return RenderCommandResult::Failure;
+        };
+        let Some(gpu_mesh) = meshes.get(*mesh_asset_id) else {
+            return RenderCommandResult::Failure;
+        };
+        let Some(instance_buffer) = instance_buffer else {
+            return RenderCommandResult::Failure;
+        };
+
+        pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..));
+
+        match &gpu_mesh.buffer_info {
+            GpuBufferInfo::Indexed {
+                buffer,
+                index_format,
+                count,
+            } => {
+                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(0..*count, 0, 0..instance_buffer.length as u32);
+            }
+            GpuBufferInfo::NonIndexed => {
+                pass.draw(0..gpu_mesh.vertex_count, 0..instance_buffer.length as u32);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
@@ -0,0 +1,300 @@
+use bevy::core_pipeline::bloom::{BloomCompositeMode, BloomPrefilterSettings, BloomSettings};
+use bevy::core_pipeline::core_2d::graph::{Core2d, Node2d};
+use bevy::core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::extract_component::{
+    ComponentUniforms, ExtractComponent, ExtractComponentPlugin, UniformComponentPlugin,
+};
+use bevy::render::render_graph::{
+    NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+};
+use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
+use bevy::render::render_resource::*;
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::texture::BevyDefault;
+use bevy::render::view::ViewTarget;
+use bevy::render::RenderApp;
+
+use super::camera::{CameraTag, UiCameraTag};
+use super::icons::events::PlayerDamageEvent;
+use super::settings::{RetroFilter, SettingsResource};
+
+/// Seconds until the chromatic aberration of a hit has faded out
+const ABERRATION_PULSE_DURATION: f32 = 0.4;
+
+pub struct PostProcessPlugin;
+
+impl Plugin for PostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<PostProcessSettings>::default(),
+            UniformComponentPlugin::<PostProcessSettings>::default(),
+        ));
+        app.add_systems(
+            Update,
+            (update_hdr_bloom_system, update_post_process_system),
+        );
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode>>(Core2d, PostProcessLabel)
+            .add_render_graph_edges(
+                Core2d,
+                (
+                    Node2d::Tonemapping,
+                    PostProcessLabel,
+                    Node2d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<PostProcessPipeline>();
+    }
+}
+
+/// Screen effects of the camera that is rendered last, so they cover the world and the HUD
+#[derive(Component, Debug, Default, Clone, Copy, ExtractComponent, ShaderType)]
+pub struct PostProcessSettings {
+    /// Offset of the red and blue channels in pixels
+    pub aberration: f32,
+    pub scanlines: f32,
+    pub crt: f32,
+    pub time: f32,
+}
+
+impl PostProcessSettings {
+    fn is_active(&self) -> bool {
+        self.aberration > 0.0 || self.scanlines > 0.0 || self.crt > 0.0
+    }
+}
+
+/// Both cameras render into the same main texture, which only works while they agree on HDR
+#[allow(clippy::type_complexity)]
+fn update_hdr_bloom_system(
+    mut commands: Commands,
+    mut camera: Query<(Entity, &mut Camera, Option<&mut BloomSettings>), With<CameraTag>>,
+    mut ui_camera: Query<(&mut Camera, &mut Tonemapping), (With<UiCameraTag>, Without<CameraTag>)>,
+    settings: Res<SettingsResource>,
+) {
+    for (entity, mut camera, bloom) in camera.iter_mut() {
+        if camera.hdr != settings.bloom {
+            camera.hdr = settings.bloom;
+        }
+        match (settings.bloom, bloom) {
+            (true, Some(mut bloom)) => {
+                if bloom.intensity != settings.bloom_intensity {
+                    bloom.intensity = settings.bloom_intensity;
+                }
+            }
+            (true, None) => {
+                // only colors brighter than white glow, the rest of the scene stays as it is
+                commands.entity(entity).insert(BloomSettings {
+                    intensity: settings.bloom_intensity,
+                    prefilter_settings: BloomPrefilterSettings {
+                        threshold: 1.0,
+                        threshold_softness: 0.3,
+                    },
+                    composite_mode: BloomCompositeMode::Additive,
+                    ..BloomSettings::OLD_SCHOOL
+                });
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            (false, None) => {}
+        }
+    }
+
+    for (mut camera, mut tonemapping) in ui_camera.iter_mut() {
+        if camera.hdr != settings.bloom {
+            camera.hdr = settings.bloom;
+        }
+        // the world is already tonemapped by the first camera in the shared HDR texture
+        let expected = if settings.bloom {
+            Tonemapping::None
+        } else {
+            Tonemapping::default()
+        };
+        if *tonemapping != expected {
+            *tonemapping = expected;
+        }
+    }
+}
+
+fn update_post_process_system(
+    mut query: Query<&mut PostProcessSettings>,
+    mut damage_events: EventReader<PlayerDamageEvent>,
+    mut pulse: Local<f32>,
+    settings: Res<SettingsResource>,
+    time: Res<Time>,
+) {
+    if damage_events.read().count() > 0 {
+        *pulse = 1.0;
+    }
+    *pulse = (*pulse - time.delta_seconds() / ABERRATION_PULSE_DURATION).max(0.0);
+
+    let aberration = if settings.chromatic_aberration && !settings.reduced_motion {
+        settings.chromatic_aberration_strength * *pulse * *pulse
+    } else {
+        0.0
+    };
+    let (scanlines, crt) = match settings.retro_filter {
+        RetroFilter::Off => (0.0, 0.0),
+        RetroFilter::Scanlines => (1.0, 0.0),
+        RetroFilter::Crt => (1.0, 1.0),
+    };
+    for mut post_process in query.iter_mut() {
+        *post_process = PostProcessSettings {
+            aberration,
+            scanlines,
+            crt,
+            // a still time stops the flicker
+            time: if settings.reduced_motion {
+                0.0
+            } else {
+                time.elapsed_seconds_wrapped()
+            },
+        };
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct PostProcessLabel;
+
+#[derive(Default)]
+struct PostProcessNode;
+
+impl ViewNode for PostProcessNode {
+    type ViewQuery = (&'static ViewTarget, &'static PostProcessSettings);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, post_process_settings): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // skip the extra pass while all effects are off
+        if !post_process_settings.is_active() {
+            return Ok(());
+        }
+
+        let post_process_pipeline = world.resource::<PostProcessPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_id = if view_target.is_hdr() {
+            post_process_pipeline.pipeline_id_hdr
+        } else {
+            post_process_pipeline.pipeline_id
+        };
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(pipeline_id) else {
+            return Ok(());
+        };
+
+        let settings_uniforms = world.resource::<ComponentUniforms<PostProcessSettings>>();
+        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "post_process_bind_group",
+            &post_process_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &post_process_pipeline.sampler,
+                settings_binding.clone(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("post_process_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct PostProcessPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    /// The main texture format depends on whether the camera renders in HDR
+    pipeline_id: CachedRenderPipelineId,
+    pipeline_id_hdr: CachedRenderPipelineId,
+}
+
+impl FromWorld for PostProcessPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "post_process_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<PostProcessSettings>(false),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let shader = world.resource::<AssetServer>().load("postprocess.wgsl");
+        let mut queue = |format: TextureFormat| {
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("post_process_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: shader.clone(),
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            format,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                })
+        };
+        let pipeline_id = queue(TextureFormat::bevy_default());
+        let pipeline_id_hdr = queue(ViewTarget::TEXTURE_FORMAT_HDR);
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+            pipeline_id_hdr,
+        }
+    }
+}
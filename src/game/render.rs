@@ -2,13 +2,9 @@ use bevy::prelude::*;
 
 pub struct RenderPlugin;
 
-const CLEAR_COLOR: &str = "#121418";
-
 impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ClearColor(
-            Color::hex(CLEAR_COLOR).expect("Invalid ClearColor!"),
-        ));
+        // the clear color is set by the theme
         app.add_systems(Startup, spawn_render_globals);
     }
 }
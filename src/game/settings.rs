@@ -1,4 +1,107 @@
 use bevy::{ecs::system::Resource, reflect::Reflect};
+use serde::{Deserialize, Serialize};
+
+/// How the movement actions control the player icon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum ControlScheme {
+    /// Up is always up on screen, left and right strafe
+    #[default]
+    ScreenRelative,
+    /// Left and right turn the icon, up and down move along its heading
+    Tank,
+}
+
+impl ControlScheme {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlScheme::ScreenRelative => "Screen Relative",
+            ControlScheme::Tank => "Tank",
+        }
+    }
+}
+
+/// Where the camera looks ahead of the player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum CameraLookAhead {
+    Off,
+    /// In the direction the player is moving
+    #[default]
+    Velocity,
+    /// In the direction the player is aiming
+    Aim,
+}
+
+impl CameraLookAhead {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraLookAhead::Off => "Off",
+            CameraLookAhead::Velocity => "Velocity",
+            CameraLookAhead::Aim => "Aim",
+        }
+    }
+}
+
+/// Retro look drawn over the world after everything else
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum RetroFilter {
+    #[default]
+    Off,
+    /// Dark lines between the rows of pixels
+    Scanlines,
+    /// Curved screen with scanlines, a shadow mask and a vignette
+    Crt,
+}
+
+impl RetroFilter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RetroFilter::Off => "Off",
+            RetroFilter::Scanlines => "Scanlines",
+            RetroFilter::Crt => "CRT",
+        }
+    }
+}
+
+/// Replaces the colors that carry meaning with ones that stay apart for colorblind players
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum ColorblindPalette {
+    #[default]
+    Off,
+    /// Deuteranopia and protanopia, followers blue and damage orange
+    RedGreen,
+    /// Tritanopia, followers pink and damage vermillion
+    BlueYellow,
+}
+
+impl ColorblindPalette {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorblindPalette::Off => "Off",
+            ColorblindPalette::RedGreen => "Red-Green",
+            ColorblindPalette::BlueYellow => "Blue-Yellow",
+        }
+    }
+}
+
+/// Shape of the circle around followers, so they are told apart without color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum FollowerMarker {
+    #[default]
+    Ring,
+    DashedRing,
+    /// Ring with a diamond badge on its upper right
+    Badge,
+}
+
+impl FollowerMarker {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FollowerMarker::Ring => "Ring",
+            FollowerMarker::DashedRing => "Dashed Ring",
+            FollowerMarker::Badge => "Badge",
+        }
+    }
+}
 
 #[derive(Resource, Debug, Clone, Reflect)]
 pub struct SettingsResource {
@@ -9,6 +112,8 @@ pub struct SettingsResource {
 
     pub avoidance_distance_dropzone: f32,
     pub avoidance_force_dropzone: f32,
+    pub avoidance_distance_obstacle: f32,
+    pub avoidance_force_obstacle: f32,
     pub avoidance_force_bounds: f32,
 
     pub max_force_distance: f32,
@@ -35,6 +140,71 @@ pub struct SettingsResource {
     pub controller_acceleration: f32,
     pub controller_dampening: f32,
     pub controller_max_speed: f32,
+    pub controller_scheme: ControlScheme,
+    /// Rotate the camera with the player heading (tank scheme only)
+    pub controller_rotate_camera: bool,
+
+    /// How fast the camera catches up with its target, 0 snaps onto it
+    pub camera_damping: f32,
+    pub camera_look_ahead: CameraLookAhead,
+    pub camera_look_ahead_distance: f32,
+    /// The target can move this far before the camera follows
+    pub camera_dead_zone: f32,
+    /// Screen shake, can be disabled for accessibility
+    pub camera_shake: bool,
+    pub camera_shake_max_offset: f32,
+    /// Max rotation in radians
+    pub camera_shake_max_angle: f32,
+    /// Trauma removed per second
+    pub camera_shake_decay: f32,
+    pub camera_shake_damage_trauma: f32,
+    /// Trauma per captured icon, once a drop-off reaches the threshold
+    pub camera_shake_capture_trauma: f32,
+    pub camera_shake_capture_threshold: u32,
+    /// Zoom factor per mouse wheel step
+    pub camera_zoom_step: f32,
+    /// How fast the zoom eases toward its target, 0 snaps onto it
+    pub camera_zoom_speed: f32,
+    /// Keep the point under the cursor in place while zooming with the mouse wheel
+    pub camera_zoom_to_cursor: bool,
+    /// Duration of the flight from the menu overview to the player, 0 skips it
+    pub camera_fly_in_duration: f32,
+
+    /// Show the minimap of the whole world
+    pub minimap: bool,
+    /// Minimap width in pixels
+    pub minimap_size: f32,
+    pub minimap_opacity: f32,
+
+    /// Only draw the icons in view of the camera
+    pub icon_culling: bool,
+
+    /// Sample the icons without filtering and mipmaps
+    pub icon_pixelated: bool,
+
+    /// HDR rendering with bloom, projectiles and particles glow
+    pub bloom: bool,
+    pub bloom_intensity: f32,
+    /// The colors split apart for a moment when the player takes damage
+    pub chromatic_aberration: bool,
+    /// Offset of the red and blue channels in pixels at the start of the pulse
+    pub chromatic_aberration_strength: f32,
+    pub retro_filter: RetroFilter,
+
+    /// Particle effects for shots, conversions, captures and damage
+    pub particles: bool,
+    /// Multiplier for the number of particles per effect
+    pub particles_amount: f32,
+    pub particles_max: u32,
+
+    pub colorblind_palette: ColorblindPalette,
+    pub follower_marker: FollowerMarker,
+    /// No screen shake, flashes, pulses, particles or camera flights
+    pub reduced_motion: bool,
+    /// Scale of the HUD screens and their text
+    pub ui_scale: f32,
+    /// Speed of the simulation, lower gives more time to react
+    pub game_speed: f32,
 
     pub max_hover_distance: f32,
     pub capture_time: f32,
@@ -63,6 +233,8 @@ impl Default for SettingsResource {
 
             avoidance_distance_dropzone: 87.0,
             avoidance_force_dropzone: 85.0,
+            avoidance_distance_obstacle: 64.0,
+            avoidance_force_obstacle: 85.0,
             avoidance_force_bounds: 0.1,
 
             velocity_time_scale: 300.0,
@@ -91,6 +263,42 @@ impl Default for SettingsResource {
             controller_acceleration: 35.0,
             controller_dampening: 210.0,
             controller_max_speed: 100.0,
+            controller_scheme: ControlScheme::ScreenRelative,
+            controller_rotate_camera: false,
+
+            camera_damping: 8.0,
+            camera_look_ahead: CameraLookAhead::Velocity,
+            camera_look_ahead_distance: 160.0,
+            camera_dead_zone: 24.0,
+            camera_shake: true,
+            camera_shake_max_offset: 32.0,
+            camera_shake_max_angle: 0.05,
+            camera_shake_decay: 1.2,
+            camera_shake_damage_trauma: 0.5,
+            camera_shake_capture_trauma: 0.06,
+            camera_shake_capture_threshold: 5,
+            camera_zoom_step: 1.25,
+            camera_zoom_speed: 10.0,
+            camera_zoom_to_cursor: true,
+            camera_fly_in_duration: 1.5,
+            minimap: true,
+            minimap_size: 200.0,
+            minimap_opacity: 0.85,
+            icon_culling: true,
+            icon_pixelated: false,
+            bloom: false,
+            bloom_intensity: 0.3,
+            chromatic_aberration: true,
+            chromatic_aberration_strength: 12.0,
+            retro_filter: RetroFilter::Off,
+            particles: true,
+            particles_amount: 1.0,
+            particles_max: 8192,
+            colorblind_palette: ColorblindPalette::Off,
+            follower_marker: FollowerMarker::Ring,
+            reduced_motion: false,
+            ui_scale: 1.0,
+            game_speed: 1.0,
 
             max_hover_distance: 880.0,
 
@@ -22,4 +22,7 @@ pub enum GameState {
 
     /// Player died or won
     GameOver,
+
+    /// Level editor, the simulation is paused
+    LevelEditor,
 }
@@ -0,0 +1,345 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::assets::themes::ThemeListAsset;
+use super::settings::{ColorblindPalette, SettingsResource};
+
+/// Color roles, every color of the world, the HUD and the shapes is one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThemeColor {
+    /// Outside of the world
+    Clear,
+    /// Fill of the world area, unless the level sets its own
+    Background,
+    BackgroundStars,
+    /// World grid, dropzone borders and the player circle
+    Grid,
+    Dropzone,
+    Obstacle,
+    ObstacleBorder,
+    /// Dropzone highlight, titles and indicators
+    Accent,
+    /// Projectiles, follower indicators and touch sticks
+    Projectile,
+    /// Lines and circles of the followers
+    Follower,
+    /// Tints of the icons, the icon sheets are white
+    IconTint,
+    FollowerTint,
+    CapturedTint,
+    /// Sparkles of captured icons
+    Capture,
+    /// Damage flash and debris
+    Damage,
+    /// Selected items in the level editor
+    Selection,
+    Text,
+    /// Behind the menu screens, over the world
+    Overlay,
+    Panel,
+    PanelSolid,
+    /// Behind the in-game HUD
+    HudPanel,
+    Bar,
+    BarFill,
+    Button,
+    ButtonHovered,
+    ButtonText,
+}
+
+impl ThemeColor {
+    pub const ALL: [ThemeColor; 26] = [
+        ThemeColor::Clear,
+        ThemeColor::Background,
+        ThemeColor::BackgroundStars,
+        ThemeColor::Grid,
+        ThemeColor::Dropzone,
+        ThemeColor::Obstacle,
+        ThemeColor::ObstacleBorder,
+        ThemeColor::Accent,
+        ThemeColor::Projectile,
+        ThemeColor::Follower,
+        ThemeColor::IconTint,
+        ThemeColor::FollowerTint,
+        ThemeColor::CapturedTint,
+        ThemeColor::Capture,
+        ThemeColor::Damage,
+        ThemeColor::Selection,
+        ThemeColor::Text,
+        ThemeColor::Overlay,
+        ThemeColor::Panel,
+        ThemeColor::PanelSolid,
+        ThemeColor::HudPanel,
+        ThemeColor::Bar,
+        ThemeColor::BarFill,
+        ThemeColor::Button,
+        ThemeColor::ButtonHovered,
+        ThemeColor::ButtonText,
+    ];
+
+    /// Color of the built-in dark theme
+    fn default_hex(&self) -> &'static str {
+        match self {
+            ThemeColor::Clear => "#121418",
+            ThemeColor::Background => "#22272e",
+            ThemeColor::BackgroundStars => "#768390aa",
+            ThemeColor::Grid => "#444c56",
+            ThemeColor::Dropzone => "#2d333b",
+            ThemeColor::Obstacle => "#373e47",
+            ThemeColor::ObstacleBorder => "#545d68",
+            ThemeColor::Accent => "#6b9894",
+            ThemeColor::Projectile => "#7fc1bb",
+            ThemeColor::Follower => "#884c56",
+            ThemeColor::IconTint => "#ffffff",
+            ThemeColor::FollowerTint => "#b2e0dc",
+            ThemeColor::CapturedTint => "#768390",
+            ThemeColor::Capture => "#daaa3f",
+            ThemeColor::Damage => "#dd4c56",
+            ThemeColor::Selection => "#f0b72f",
+            ThemeColor::Text => "#adbacb",
+            ThemeColor::Overlay => "#12141844",
+            ThemeColor::Panel => "#121418CC",
+            ThemeColor::PanelSolid => "#121418DD",
+            ThemeColor::HudPanel => "#22272eDD",
+            ThemeColor::Bar => "#3c454f",
+            ThemeColor::BarFill => "#56837f",
+            ThemeColor::Button => "#2d333b",
+            ThemeColor::ButtonHovered => "#3c454f",
+            ThemeColor::ButtonText => "#7fc1bb",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    colors: [Color; ThemeColor::ALL.len()],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "Dark".to_string(),
+            colors: ThemeColor::ALL.map(|color| Color::hex(color.default_hex()).unwrap()),
+        }
+    }
+}
+
+impl Theme {
+    /// Colors missing from `colors` are taken from the built-in dark theme
+    pub fn from_hex(name: String, colors: &HashMap<ThemeColor, String>) -> Result<Self, String> {
+        let mut theme = Theme {
+            name,
+            ..Default::default()
+        };
+        for (color, hex) in colors.iter() {
+            theme.colors[*color as usize] =
+                Color::hex(hex).map_err(|_| format!("invalid color {:?} for {:?}", hex, color))?;
+        }
+        Ok(theme)
+    }
+
+    pub fn color(&self, color: ThemeColor) -> Color {
+        self.colors[color as usize]
+    }
+
+    /// Linear rgba, as used by the shaders
+    pub fn linear(&self, color: ThemeColor) -> Vec4 {
+        Vec4::from(self.color(color).as_linear_rgba_f32())
+    }
+}
+
+impl ColorblindPalette {
+    /// Colors from the Okabe-Ito palette that replace the ones of the theme
+    fn overrides(&self) -> &'static [(ThemeColor, &'static str)] {
+        match self {
+            ColorblindPalette::Off => &[],
+            ColorblindPalette::RedGreen => &[
+                (ThemeColor::Follower, "#0072b2"),
+                (ThemeColor::FollowerTint, "#56b4e9"),
+                (ThemeColor::Projectile, "#f0e442"),
+                (ThemeColor::Capture, "#e69f00"),
+                (ThemeColor::Damage, "#d55e00"),
+            ],
+            ColorblindPalette::BlueYellow => &[
+                (ThemeColor::Follower, "#cc79a7"),
+                (ThemeColor::FollowerTint, "#f2c1dc"),
+                (ThemeColor::Projectile, "#009e73"),
+                (ThemeColor::Capture, "#56b4e9"),
+                (ThemeColor::Damage, "#d55e00"),
+            ],
+        }
+    }
+}
+
+/// A theme color with the alpha and brightness of one use of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThemePaint {
+    pub color: ThemeColor,
+    /// Multiplies the alpha of the theme color
+    pub alpha: f32,
+    /// Brightness multiplier, above 1 glows with bloom
+    pub glow: f32,
+}
+
+impl From<ThemeColor> for ThemePaint {
+    fn from(color: ThemeColor) -> Self {
+        Self {
+            color,
+            alpha: 1.0,
+            glow: 1.0,
+        }
+    }
+}
+
+impl ThemePaint {
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_glow(mut self, glow: f32) -> Self {
+        self.glow = glow;
+        self
+    }
+
+    pub fn resolve(&self, theme: &Theme) -> Color {
+        let color = theme.color(self.color);
+        let color = color.with_a(color.a() * self.alpha);
+        if self.glow == 1.0 {
+            return color;
+        }
+        let [r, g, b, a] = color.as_linear_rgba_f32();
+        Color::rgba_linear(r * self.glow, g * self.glow, b * self.glow, a)
+    }
+}
+
+/// Stroke of a shape that is restyled when the theme changes
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedStroke(pub ThemePaint);
+
+/// Fill of a shape that is restyled when the theme changes
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedFill(pub ThemePaint);
+
+/// Background of a UI node that is restyled when the theme changes
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedBackground(pub ThemeColor);
+
+/// Color of every section of a text that is restyled when the theme changes
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedText(pub ThemeColor);
+
+#[derive(Resource, Debug)]
+pub struct ThemeResource {
+    pub handle: Handle<ThemeListAsset>,
+    /// Themes that loaded, the built-in dark theme until then
+    themes: Vec<Theme>,
+    current: usize,
+    palette: ColorblindPalette,
+    /// The current theme with the colorblind palette applied
+    active: Theme,
+}
+
+impl Default for ThemeResource {
+    fn default() -> Self {
+        Self {
+            handle: Handle::default(),
+            themes: vec![Theme::default()],
+            current: 0,
+            palette: ColorblindPalette::Off,
+            active: Theme::default(),
+        }
+    }
+}
+
+impl ThemeResource {
+    pub fn current(&self) -> &Theme {
+        &self.active
+    }
+
+    pub fn color(&self, color: ThemeColor) -> Color {
+        self.current().color(color)
+    }
+
+    /// Replace the themes and switch to the first one
+    pub fn set_themes(&mut self, themes: Vec<Theme>) {
+        self.themes = themes;
+        self.current = 0;
+        self.refresh();
+    }
+
+    /// Switch to the next theme, wrapping around
+    pub fn cycle(&mut self) {
+        self.current = (self.current + 1) % self.themes.len();
+        self.refresh();
+    }
+
+    pub fn set_palette(&mut self, palette: ColorblindPalette) {
+        self.palette = palette;
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        let mut theme = self.themes[self.current.min(self.themes.len() - 1)].clone();
+        for (color, hex) in self.palette.overrides() {
+            theme.colors[*color as usize] = Color::hex(hex).unwrap();
+        }
+        self.active = theme;
+    }
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        let theme = ThemeResource::default();
+        app.insert_resource(ClearColor(theme.color(ThemeColor::Clear)));
+        app.insert_resource(theme);
+        app.add_systems(
+            PostUpdate,
+            (
+                sync_palette_system,
+                apply_theme_system.run_if(resource_changed::<ThemeResource>),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn sync_palette_system(settings: Res<SettingsResource>, mut theme: ResMut<ThemeResource>) {
+    if theme.palette != settings.colorblind_palette {
+        theme.set_palette(settings.colorblind_palette);
+    }
+}
+
+/// Restyle the live entities with the colors of the current theme
+#[allow(clippy::type_complexity)]
+fn apply_theme_system(
+    theme: Res<ThemeResource>,
+    mut clear_color: ResMut<ClearColor>,
+    mut strokes: Query<(&ThemedStroke, &mut Stroke)>,
+    mut fills: Query<(&ThemedFill, &mut Fill)>,
+    mut backgrounds: Query<(&ThemedBackground, &mut BackgroundColor)>,
+    mut texts: Query<(&ThemedText, &mut Text)>,
+) {
+    let theme = theme.current();
+    clear_color.0 = theme.color(ThemeColor::Clear);
+    for (ThemedStroke(paint), mut stroke) in strokes.iter_mut() {
+        stroke.color = paint.resolve(theme);
+    }
+    for (ThemedFill(paint), mut fill) in fills.iter_mut() {
+        fill.color = paint.resolve(theme);
+    }
+    for (ThemedBackground(color), mut background) in backgrounds.iter_mut() {
+        background.0 = theme.color(*color);
+    }
+    for (ThemedText(color), mut text) in texts.iter_mut() {
+        for section in text.sections.iter_mut() {
+            section.style.color = theme.color(*color);
+        }
+    }
+}
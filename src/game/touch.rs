@@ -0,0 +1,307 @@
+use bevy::input::touch::TouchInput;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use bevy::window::PrimaryWindow;
+use bevy_prototype_lyon::prelude::*;
+
+use super::camera::{UiCameraTag, CAMERA_LAYER_UI};
+use super::controls::{InputAction, VirtualInputResource};
+use super::states::GameState;
+use super::theme::{ThemeColor, ThemePaint, ThemeResource, ThemedFill, ThemedStroke};
+
+const STICK_RADIUS: f32 = 80.0;
+const STICK_KNOB_RADIUS: f32 = 32.0;
+const FIRE_RADIUS: f32 = 48.0;
+const MARGIN: f32 = 48.0;
+/// Touches starting this far from the center of a control still grab it
+const GRAB_RADIUS_FACTOR: f32 = 1.5;
+/// Aim stick needs to be moved this far (0-1) before the player rotates
+const AIM_DEAD_ZONE: f32 = 0.2;
+
+#[derive(Default, Debug)]
+struct TouchStick {
+    touch: Option<u64>,
+    /// Stick offset, x is right and y is up, with a length of up to 1
+    vector: Vec2,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct TouchControlsResource {
+    /// Set once the first touch was detected, the on-screen controls are only shown after that
+    pub enabled: bool,
+    movement: TouchStick,
+    aim: TouchStick,
+    fire_touch: Option<u64>,
+    pinch_distance: Option<f32>,
+}
+
+/// Center of each control in logical window coordinates (origin in the upper left corner)
+struct TouchLayout {
+    movement: Vec2,
+    aim: Vec2,
+    fire: Vec2,
+}
+
+impl TouchLayout {
+    fn new(window: &Window) -> Self {
+        let bottom = window.height() - MARGIN - STICK_RADIUS;
+        let aim = Vec2::new(window.width() - MARGIN - STICK_RADIUS, bottom);
+        Self {
+            movement: Vec2::new(MARGIN + STICK_RADIUS, bottom),
+            aim,
+            fire: aim - Vec2::new(0.0, STICK_RADIUS + FIRE_RADIUS + MARGIN / 2.0),
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum TouchControlShape {
+    MovementBase,
+    MovementKnob,
+    AimBase,
+    AimKnob,
+    Fire,
+}
+
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TouchControlsResource::default());
+        app.add_systems(Update, detect_touch_system);
+        // virtual input is updated before any gameplay system reads it
+        app.add_systems(
+            PreUpdate,
+            update_touch_controls_system
+                .after(InputSystem)
+                .run_if(in_state(GameState::GameRunning).and_then(touch_controls_enabled)),
+        );
+        app.add_systems(
+            Update,
+            render_touch_controls_system
+                .run_if(in_state(GameState::GameRunning).and_then(touch_controls_enabled)),
+        );
+        app.add_systems(OnExit(GameState::GameRunning), hide_touch_controls_system);
+    }
+}
+
+fn touch_controls_enabled(touch_controls: Res<TouchControlsResource>) -> bool {
+    touch_controls.enabled
+}
+
+fn detect_touch_system(
+    mut events: EventReader<TouchInput>,
+    mut touch_controls: ResMut<TouchControlsResource>,
+) {
+    if events.read().count() > 0 && !touch_controls.enabled {
+        info!("Touch input detected, enable on-screen controls");
+        touch_controls.enabled = true;
+    }
+}
+
+/// Update the stick vector from the touch, releases the stick if the touch ended.
+fn update_stick(stick: &mut TouchStick, touches: &Touches, center: Vec2) {
+    if let Some(touch) = stick.touch.and_then(|id| touches.get_pressed(id)) {
+        let offset = (touch.position() - center) / STICK_RADIUS;
+        // window coordinates are y down
+        stick.vector = Vec2::new(offset.x, -offset.y).clamp_length_max(1.0);
+    } else {
+        stick.touch = None;
+        stick.vector = Vec2::ZERO;
+    }
+}
+
+fn update_touch_controls_system(
+    touches: Res<Touches>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut touch_controls: ResMut<TouchControlsResource>,
+    mut virtual_input: ResMut<VirtualInputResource>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let layout = TouchLayout::new(window);
+    let touch_controls = touch_controls.as_mut();
+    virtual_input.just_pressed.clear();
+
+    // assign new touches to the control they started on
+    for touch in touches.iter_just_pressed() {
+        let position = touch.position();
+        let grabs =
+            |center: Vec2, radius: f32| position.distance(center) <= radius * GRAB_RADIUS_FACTOR;
+        if touch_controls.fire_touch.is_none() && grabs(layout.fire, FIRE_RADIUS) {
+            touch_controls.fire_touch = Some(touch.id());
+            virtual_input.just_pressed.insert(InputAction::Shoot);
+        } else if touch_controls.movement.touch.is_none() && grabs(layout.movement, STICK_RADIUS) {
+            touch_controls.movement.touch = Some(touch.id());
+        } else if touch_controls.aim.touch.is_none() && grabs(layout.aim, STICK_RADIUS) {
+            touch_controls.aim.touch = Some(touch.id());
+        }
+    }
+
+    update_stick(&mut touch_controls.movement, &touches, layout.movement);
+    update_stick(&mut touch_controls.aim, &touches, layout.aim);
+
+    if touch_controls
+        .fire_touch
+        .and_then(|id| touches.get_pressed(id))
+        .is_none()
+    {
+        touch_controls.fire_touch = None;
+    }
+
+    // two touches outside of the controls pinch to zoom
+    let assigned = [
+        touch_controls.movement.touch,
+        touch_controls.aim.touch,
+        touch_controls.fire_touch,
+    ];
+    let free = touches
+        .iter()
+        .filter(|touch| !assigned.contains(&Some(touch.id())))
+        .take(2)
+        .map(|touch| touch.position())
+        .collect::<Vec<_>>();
+    virtual_input.zoom = 1.0;
+    if let [first, second] = free[..] {
+        let distance = first.distance(second);
+        if let Some(previous) = touch_controls.pinch_distance {
+            if distance > 0.0 {
+                virtual_input.zoom = previous / distance;
+            }
+        }
+        touch_controls.pinch_distance = Some(distance);
+    } else {
+        touch_controls.pinch_distance = None;
+    }
+
+    virtual_input.movement = touch_controls.movement.vector;
+    virtual_input.aim = if touch_controls.aim.vector.length() > AIM_DEAD_ZONE {
+        Some(touch_controls.aim.vector)
+    } else {
+        None
+    };
+    if touch_controls.fire_touch.is_some() {
+        virtual_input.pressed.insert(InputAction::Shoot);
+    } else {
+        virtual_input.pressed.remove(&InputAction::Shoot);
+    }
+}
+
+fn spawn_touch_control_shape(
+    commands: &mut Commands,
+    theme: &ThemeResource,
+    shape: TouchControlShape,
+) {
+    let (radius, stroke, fill) = match shape {
+        TouchControlShape::MovementBase | TouchControlShape::AimBase => (
+            STICK_RADIUS,
+            ThemeColor::Accent,
+            ThemePaint::from(ThemeColor::Background).with_alpha(0.53),
+        ),
+        TouchControlShape::MovementKnob | TouchControlShape::AimKnob => (
+            STICK_KNOB_RADIUS,
+            ThemeColor::Projectile,
+            ThemePaint::from(ThemeColor::BarFill).with_alpha(0.67),
+        ),
+        TouchControlShape::Fire => (
+            FIRE_RADIUS,
+            ThemeColor::Damage,
+            ThemePaint::from(ThemeColor::Follower).with_alpha(0.67),
+        ),
+    };
+    let mut builder = GeometryBuilder::new();
+    builder = builder.add(&shapes::Circle {
+        radius,
+        center: Vec2::ZERO,
+    });
+    commands.spawn((
+        ShapeBundle {
+            path: builder.build(),
+            spatial: SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Stroke::new(theme.color(stroke), 3.0),
+        Fill::color(fill.resolve(theme.current())),
+        ThemedStroke(stroke.into()),
+        ThemedFill(fill),
+        RenderLayers::layer(CAMERA_LAYER_UI),
+        NoFrustumCulling,
+        shape,
+    ));
+}
+
+fn render_touch_controls_system(
+    mut commands: Commands,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<&OrthographicProjection, With<UiCameraTag>>,
+    touch_controls: Res<TouchControlsResource>,
+    theme: Res<ThemeResource>,
+    mut shapes: Query<(&mut Transform, &mut Visibility, &TouchControlShape)>,
+) {
+    if shapes.is_empty() {
+        for shape in [
+            TouchControlShape::MovementBase,
+            TouchControlShape::AimBase,
+            TouchControlShape::Fire,
+            TouchControlShape::MovementKnob,
+            TouchControlShape::AimKnob,
+        ] {
+            spawn_touch_control_shape(&mut commands, &theme, shape);
+        }
+        return;
+    }
+
+    let (Ok(window), Ok(projection)) = (window.get_single(), camera.get_single()) else {
+        return;
+    };
+    let layout = TouchLayout::new(window);
+
+    // window coordinates to the world space of the ui camera
+    let scale = projection.scale;
+    let to_ui = |position: Vec2| {
+        Vec2::new(
+            position.x - window.width() / 2.0,
+            window.height() / 2.0 - position.y,
+        ) * scale
+    };
+
+    for (mut transform, mut visibility, shape) in shapes.iter_mut() {
+        let (position, z) = match shape {
+            TouchControlShape::MovementBase => (to_ui(layout.movement), 0.0),
+            TouchControlShape::AimBase => (to_ui(layout.aim), 0.0),
+            TouchControlShape::Fire => (to_ui(layout.fire), 0.0),
+            TouchControlShape::MovementKnob => (
+                to_ui(layout.movement) + touch_controls.movement.vector * STICK_RADIUS * scale,
+                1.0,
+            ),
+            TouchControlShape::AimKnob => (
+                to_ui(layout.aim) + touch_controls.aim.vector * STICK_RADIUS * scale,
+                1.0,
+            ),
+        };
+        transform.translation = position.extend(z);
+        transform.scale = Vec3::splat(scale);
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_touch_controls_system(
+    mut touch_controls: ResMut<TouchControlsResource>,
+    mut virtual_input: ResMut<VirtualInputResource>,
+    mut shapes: Query<&mut Visibility, With<TouchControlShape>>,
+) {
+    for mut visibility in shapes.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    let enabled = touch_controls.enabled;
+    *touch_controls = TouchControlsResource {
+        enabled,
+        ..Default::default()
+    };
+    *virtual_input = VirtualInputResource::default();
+}
@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::Obstacle;
+use crate::game::background::BackgroundStyle;
+
+/// Which icons of the icon sheets are spawned, by name.
+/// Patterns may contain `*` wildcards to select groups of icons like `arrow*`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IconFilter {
+    #[serde(default = "IconFilter::all")]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl Default for IconFilter {
+    fn default() -> Self {
+        Self {
+            include: Self::all(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// Match `name` against a pattern with `*` wildcards
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // no wildcard, exact match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+impl IconFilter {
+    fn all() -> Vec<String> {
+        vec!["*".to_string()]
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.include
+            .iter()
+            .any(|pattern| matches_pattern(pattern, name))
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| matches_pattern(pattern, name))
+    }
+}
+
+/// When the game is won
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WinCondition {
+    /// All icons are captured in a dropzone
+    #[default]
+    CaptureAll,
+    /// Reach the score
+    Score { score: u32 },
+    /// Capture this many icons
+    Capture { count: usize },
+}
+
+impl WinCondition {
+    /// `total` is the number of icons that can be captured
+    pub fn is_met(&self, score: u32, captured: usize, total: usize) -> bool {
+        match self {
+            WinCondition::CaptureAll => captured >= total,
+            WinCondition::Score { score: target } => score >= *target,
+            WinCondition::Capture { count } => captured >= (*count).min(total),
+        }
+    }
+}
+
+/// Dropzone of a [`WorldLayout`], captured icons are arranged in a grid inside of it
+#[derive(Debug, Clone)]
+pub struct DropzoneLayout {
+    pub center: Vec2,
+    pub size: Vec2,
+    /// Drift in units per second, the dropzone bounces off the world bounds
+    pub velocity: Vec2,
+    /// Units per second the dropzone shrinks on each axis
+    pub shrink_rate: f32,
+    /// The dropzone stops shrinking at this size
+    pub min_size: Vec2,
+}
+
+impl DropzoneLayout {
+    /// Static dropzone
+    pub fn new(center: Vec2, size: Vec2) -> Self {
+        Self {
+            center,
+            size,
+            velocity: Vec2::ZERO,
+            shrink_rate: 0.0,
+            min_size: size,
+        }
+    }
+}
+
+/// Area free icons are spawned in
+#[derive(Debug, Clone)]
+pub struct SpawnRegion {
+    pub center: Vec2,
+    pub size: Vec2,
+}
+
+impl SpawnRegion {
+    pub fn min(&self) -> Vec2 {
+        self.center - self.size / 2.0
+    }
+
+    pub fn max(&self) -> Vec2 {
+        self.center + self.size / 2.0
+    }
+
+    pub fn corners(&self) -> [Vec2; 4] {
+        let (min, max) = (self.min(), self.max());
+        [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+    }
+}
+
+/// Level definition, the world is centered around the origin
+#[derive(Debug, Clone)]
+pub struct WorldLayout {
+    pub name: String,
+    pub description: String,
+    pub size: Vec2,
+    pub grid_spacing: f32,
+    pub player_start: Vec2,
+    /// Name of the icon controlled by the player
+    pub player_icon: String,
+    pub icons: IconFilter,
+    pub dropzones: Vec<DropzoneLayout>,
+    pub obstacles: Vec<Obstacle>,
+    /// Icons spawn anywhere in the world if there are none
+    pub spawn_regions: Vec<SpawnRegion>,
+    /// Icons, the player and projectiles leaving the world re-enter on the opposite side
+    pub wrap: bool,
+    pub background: BackgroundStyle,
+    /// Overrides fields of the `SettingsResource` by name
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    pub win_condition: WinCondition,
+}
+
+impl WorldLayout {
+    /// Square world with a single dropzone in the center
+    pub fn classic() -> Self {
+        Self {
+            name: "Classic".to_string(),
+            description: "One dropzone in the center of the world".to_string(),
+            size: Vec2::splat(1024.0 * 10.0),
+            grid_spacing: 512.0,
+            player_start: Vec2::new(0.0, 512.0),
+            player_icon: "rust".to_string(),
+            icons: IconFilter::default(),
+            dropzones: vec![DropzoneLayout::new(Vec2::ZERO, Vec2::splat(2048.0))],
+            obstacles: Vec::new(),
+            spawn_regions: Vec::new(),
+            wrap: false,
+            background: BackgroundStyle::default(),
+            settings: Default::default(),
+            win_condition: WinCondition::CaptureAll,
+        }
+    }
+}
+
+/// Available levels and the one used for the next game, the bundled levels
+/// replace the built-in default once they are loaded
+#[derive(Resource, Debug)]
+pub struct WorldLayoutResource {
+    pub layouts: Vec<WorldLayout>,
+    pub current: usize,
+}
+
+impl Default for WorldLayoutResource {
+    fn default() -> Self {
+        Self {
+            layouts: vec![WorldLayout::classic()],
+            current: 0,
+        }
+    }
+}
+
+impl WorldLayoutResource {
+    pub fn current(&self) -> &WorldLayout {
+        &self.layouts[self.current.min(self.layouts.len() - 1)]
+    }
+}
@@ -1,9 +1,69 @@
 use super::camera::{CAMERA_LAYER, CAMERA_Z_BACKGROUND};
+use super::settings::SettingsResource;
 use super::states::GameState;
+use super::theme::{ThemeColor, ThemeResource, ThemedFill, ThemedStroke};
 use bevy::prelude::*;
+use bevy::reflect::Struct;
 use bevy::render::batching::NoAutomaticBatching;
 use bevy::render::view::RenderLayers;
 use bevy_prototype_lyon::prelude::*;
+use serde_json::{Map, Value};
+
+mod layout;
+mod obstacle;
+
+pub use layout::{
+    DropzoneLayout, IconFilter, SpawnRegion, WinCondition, WorldLayout, WorldLayoutResource,
+};
+pub use obstacle::Obstacle;
+
+#[derive(Debug, Clone)]
+pub struct Dropzone {
+    /// Lower left corner of the drop zone
+    pub min: Vec2,
+    /// Upper right corner of the drop zone
+    pub max: Vec2,
+    /// Drift in units per second, bounces off the world bounds
+    pub velocity: Vec2,
+    /// Units per second the drop zone shrinks on each axis
+    pub shrink_rate: f32,
+    pub min_size: Vec2,
+}
+
+impl Dropzone {
+    /// Returns true if the point lies within the min/max bounds
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    fn is_animated(&self) -> bool {
+        self.velocity != Vec2::ZERO
+            || (self.shrink_rate > 0.0 && self.size().cmpgt(self.min_size).any())
+    }
+}
+
+impl From<&DropzoneLayout> for Dropzone {
+    fn from(layout: &DropzoneLayout) -> Self {
+        Self {
+            min: layout.center - layout.size / 2.0,
+            max: layout.center + layout.size / 2.0,
+            velocity: layout.velocity,
+            shrink_rate: layout.shrink_rate,
+            min_size: layout.min_size.min(layout.size),
+        }
+    }
+}
 
 #[derive(Resource)]
 pub struct WorldBoundaryResource {
@@ -12,34 +72,55 @@ pub struct WorldBoundaryResource {
     pub bounds_min: Vec2,
     /// Lower Right corner of the game world
     pub bounds_max: Vec2,
-    /// Upper Left corner of the drop zone
-    pub dropzone_min: Vec2,
-    /// Lower Right corner of the drop zone
-    pub dropzone_max: Vec2,
+    pub player_start: Vec2,
+    pub dropzones: Vec<Dropzone>,
+    pub obstacles: Vec<Obstacle>,
+    pub spawn_regions: Vec<SpawnRegion>,
+    /// Leaving the world through an edge re-enters it on the opposite side
+    pub wrap: bool,
 }
 
 impl Default for WorldBoundaryResource {
     fn default() -> Self {
-        let world = 1024.0 * 5.0;
-        let dropzone = 1024.0;
+        Self::from(&WorldLayout::classic())
+    }
+}
 
+impl From<&WorldLayout> for WorldBoundaryResource {
+    fn from(layout: &WorldLayout) -> Self {
         Self {
-            grid_spacing: 512.0,
-            bounds_min: Vec2::splat(-world),
-            bounds_max: Vec2::splat(world),
-            dropzone_min: Vec2::splat(-dropzone),
-            dropzone_max: Vec2::splat(dropzone),
+            grid_spacing: layout.grid_spacing,
+            bounds_min: -layout.size / 2.0,
+            bounds_max: layout.size / 2.0,
+            player_start: layout.player_start,
+            dropzones: layout.dropzones.iter().map(Dropzone::from).collect(),
+            obstacles: layout.obstacles.clone(),
+            spawn_regions: layout.spawn_regions.clone(),
+            wrap: layout.wrap,
         }
     }
 }
 
 impl WorldBoundaryResource {
-    /// Returns true if the point lies within the dropzone min/max bounds
+    /// Returns true if the point lies within any of the dropzones
     pub fn in_dropzone(&self, point: Vec2) -> bool {
-        point.x >= self.dropzone_min.x
-            && point.x <= self.dropzone_max.x
-            && point.y >= self.dropzone_min.y
-            && point.y <= self.dropzone_max.y
+        self.dropzones
+            .iter()
+            .any(|dropzone| dropzone.contains(point))
+    }
+
+    /// Index of the dropzone the point lies in
+    pub fn dropzone_at(&self, point: Vec2) -> Option<usize> {
+        self.dropzones
+            .iter()
+            .position(|dropzone| dropzone.contains(point))
+    }
+
+    /// Returns true if the point lies within any of the obstacles
+    pub fn in_obstacle(&self, point: Vec2) -> bool {
+        self.obstacles
+            .iter()
+            .any(|obstacle| obstacle.contains(point))
     }
 
     pub fn in_bounds(&self, point: Vec2) -> bool {
@@ -52,48 +133,161 @@ impl WorldBoundaryResource {
     pub fn size(&self) -> Vec2 {
         self.bounds_max - self.bounds_min
     }
+
+    /// Moves a point outside of a wrapping world back in from the opposite side
+    pub fn wrap_position(&self, point: Vec2) -> Vec2 {
+        if !self.wrap {
+            return point;
+        }
+        self.bounds_min + (point - self.bounds_min).rem_euclid(self.size())
+    }
+
+    /// Shortest offset between two points, across the edges of a wrapping world
+    pub fn delta(&self, from: Vec2, to: Vec2) -> Vec2 {
+        let delta = to - from;
+        if !self.wrap {
+            return delta;
+        }
+        delta - self.size() * (delta / self.size()).round()
+    }
+}
+
+/// Grid and background of the world, rebuilt with the layout
+#[derive(Component)]
+struct WorldShapeTag;
+
+#[derive(Component)]
+struct DropzoneShape {
+    index: usize,
+    /// Size of the shape path, the transform scales it to the current size
+    base_size: Vec2,
 }
 
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
+        app.insert_resource(WorldLayoutResource::default());
         app.insert_resource(WorldBoundaryResource::default());
         app.add_plugins(ShapePlugin);
         app.add_systems(OnEnter(GameState::GameLoading), setup_world_grid);
+        app.add_systems(OnEnter(GameState::MainMenu), reset_dropzones_system);
+        app.add_systems(
+            Update,
+            update_dropzones_system.run_if(in_state(GameState::GameRunning)),
+        );
+        app.add_systems(
+            Update,
+            update_dropzone_shapes_system.run_if(resource_changed::<WorldBoundaryResource>),
+        );
     }
 }
 
+/// Overwrite settings fields by name, returns the previous values of the fields
+fn override_settings(
+    settings: &mut SettingsResource,
+    values: &Map<String, Value>,
+) -> Map<String, Value> {
+    let mut previous = Map::new();
+    for (name, value) in values {
+        let Some(field) = settings.field_mut(name) else {
+            warn!("Unknown setting in level: {}", name);
+            continue;
+        };
+        let old_value = if let Some(field) = field.downcast_mut::<f32>() {
+            value
+                .as_f64()
+                .map(|value| Value::from(std::mem::replace(field, value as f32)))
+        } else if let Some(field) = field.downcast_mut::<u32>() {
+            value
+                .as_u64()
+                .map(|value| Value::from(std::mem::replace(field, value as u32)))
+        } else if let Some(field) = field.downcast_mut::<i32>() {
+            value
+                .as_i64()
+                .map(|value| Value::from(std::mem::replace(field, value as i32)))
+        } else if let Some(field) = field.downcast_mut::<bool>() {
+            value
+                .as_bool()
+                .map(|value| Value::from(std::mem::replace(field, value)))
+        } else {
+            None
+        };
+        match old_value {
+            Some(old_value) => {
+                previous.insert(name.clone(), old_value);
+            }
+            None => warn!("Unsupported value for setting {}: {}", name, value),
+        }
+    }
+    previous
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
 fn setup_world_grid(
     mut commands: Commands,
-    boundary: Res<WorldBoundaryResource>,
+    layouts: Res<WorldLayoutResource>,
+    mut boundary: ResMut<WorldBoundaryResource>,
+    mut settings: ResMut<SettingsResource>,
+    shapes: Query<Entity, Or<(With<WorldShapeTag>, With<DropzoneShape>)>>,
     mut state: ResMut<NextState<GameState>>,
+    theme: Res<ThemeResource>,
+    // settings as they were before the current level overrode them
+    mut overridden_settings: Local<Map<String, Value>>,
 ) {
+    for entity in shapes.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let layout = layouts.current();
+    *boundary = WorldBoundaryResource::from(layout);
+
+    override_settings(&mut settings, &overridden_settings);
+    *overridden_settings = override_settings(&mut settings, &layout.settings);
+
     let width = boundary.bounds_max.x - boundary.bounds_min.x;
     let height = boundary.bounds_max.y - boundary.bounds_min.y;
 
+    // a wrapping world repeats, so the grid continues past the edges
+    // with the neighbouring copies of the world
+    let (margin, tiles) = if boundary.wrap {
+        (boundary.size() / 2.0, [-1.0, 0.0, 1.0].as_slice())
+    } else {
+        (Vec2::ZERO, [0.0].as_slice())
+    };
+    let grid_min = boundary.bounds_min - margin;
+    let grid_max = boundary.bounds_max + margin;
+
     let mut builder = GeometryBuilder::new();
     builder = builder.add(&shapes::Rectangle {
-        extents: Vec2::new(width, height),
+        extents: grid_max - grid_min,
         origin: RectangleOrigin::Center,
     });
 
-    let mut x = boundary.bounds_min.x;
-    while x < boundary.bounds_max.x {
-        builder = builder.add(&shapes::Line(
-            Vec2::new(x, boundary.bounds_min.y),
-            Vec2::new(x, boundary.bounds_max.y),
-        ));
-        x += boundary.grid_spacing;
-    }
+    for tile in tiles {
+        let mut x = boundary.bounds_min.x;
+        while x < boundary.bounds_max.x {
+            let tiled_x = x + tile * width;
+            if tiled_x >= grid_min.x && tiled_x < grid_max.x {
+                builder = builder.add(&shapes::Line(
+                    Vec2::new(tiled_x, grid_min.y),
+                    Vec2::new(tiled_x, grid_max.y),
+                ));
+            }
+            x += boundary.grid_spacing;
+        }
 
-    let mut y = boundary.bounds_min.y;
-    while y < boundary.bounds_max.y {
-        builder = builder.add(&shapes::Line(
-            Vec2::new(boundary.bounds_min.x, y),
-            Vec2::new(boundary.bounds_max.x, y),
-        ));
-        y += boundary.grid_spacing;
+        let mut y = boundary.bounds_min.y;
+        while y < boundary.bounds_max.y {
+            let tiled_y = y + tile * height;
+            if tiled_y >= grid_min.y && tiled_y < grid_max.y {
+                builder = builder.add(&shapes::Line(
+                    Vec2::new(grid_min.x, tiled_y),
+                    Vec2::new(grid_max.x, tiled_y),
+                ));
+            }
+            y += boundary.grid_spacing;
+        }
     }
 
     commands.spawn((
@@ -109,34 +303,134 @@ fn setup_world_grid(
             },
             ..Default::default()
         },
-        Fill::color(Color::hex("#22272e").unwrap()),
-        Stroke::new(Color::hex("#444c56").unwrap(), 1.0),
+        // the fill is drawn by the background
+        Stroke::new(theme.color(ThemeColor::Grid), 1.0),
+        ThemedStroke(ThemeColor::Grid.into()),
         RenderLayers::layer(CAMERA_LAYER),
         NoAutomaticBatching,
+        WorldShapeTag,
     ));
 
-    let mut builder = GeometryBuilder::new();
-    let width = boundary.dropzone_max.x - boundary.dropzone_min.x;
-    let height = boundary.dropzone_max.y - boundary.dropzone_min.y;
-    builder = builder.add(&shapes::Rectangle {
-        extents: Vec2::new(width, height),
-        origin: RectangleOrigin::Center,
-    });
+    if !boundary.obstacles.is_empty() {
+        let builder = boundary
+            .obstacles
+            .iter()
+            .fold(GeometryBuilder::new(), |builder, obstacle| {
+                obstacle.add_to_builder(builder)
+            });
 
-    commands.spawn((
-        ShapeBundle {
-            path: builder.build(),
-            spatial: SpatialBundle {
-                transform: Transform::from_translation(Vec3::new(0.0, 0.0, CAMERA_Z_BACKGROUND)),
+        commands.spawn((
+            ShapeBundle {
+                path: builder.build(),
+                spatial: SpatialBundle {
+                    transform: Transform::from_translation(Vec3::new(
+                        0.0,
+                        0.0,
+                        CAMERA_Z_BACKGROUND + 1.0,
+                    )),
+                    ..Default::default()
+                },
                 ..Default::default()
             },
-            ..Default::default()
-        },
-        Fill::color(Color::hex("#2d333b").unwrap()),
-        Stroke::new(Color::hex("#444c56").unwrap(), 1.0),
-        RenderLayers::layer(CAMERA_LAYER),
-        NoAutomaticBatching,
-    ));
+            Fill::color(theme.color(ThemeColor::Obstacle)),
+            Stroke::new(theme.color(ThemeColor::ObstacleBorder), 4.0),
+            ThemedFill(ThemeColor::Obstacle.into()),
+            ThemedStroke(ThemeColor::ObstacleBorder.into()),
+            RenderLayers::layer(CAMERA_LAYER),
+            NoAutomaticBatching,
+            WorldShapeTag,
+        ));
+    }
+
+    for (index, dropzone) in boundary.dropzones.iter().enumerate() {
+        let mut builder = GeometryBuilder::new();
+        builder = builder.add(&shapes::Rectangle {
+            extents: dropzone.size(),
+            origin: RectangleOrigin::Center,
+        });
+
+        commands.spawn((
+            ShapeBundle {
+                path: builder.build(),
+                spatial: SpatialBundle {
+                    transform: Transform::from_translation(
+                        dropzone.center().extend(CAMERA_Z_BACKGROUND),
+                    ),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Fill::color(theme.color(ThemeColor::Dropzone)),
+            Stroke::new(theme.color(ThemeColor::Grid), 1.0),
+            ThemedFill(ThemeColor::Dropzone.into()),
+            ThemedStroke(ThemeColor::Grid.into()),
+            RenderLayers::layer(CAMERA_LAYER),
+            NoAutomaticBatching,
+            DropzoneShape {
+                index,
+                base_size: dropzone.size(),
+            },
+        ));
+    }
 
     state.set(GameState::MainMenu);
 }
+
+/// Moving dropzones start over at their initial position with every game
+fn reset_dropzones_system(
+    layouts: Res<WorldLayoutResource>,
+    mut boundary: ResMut<WorldBoundaryResource>,
+) {
+    boundary.dropzones = layouts
+        .current()
+        .dropzones
+        .iter()
+        .map(Dropzone::from)
+        .collect();
+}
+
+fn update_dropzones_system(mut boundary: ResMut<WorldBoundaryResource>, time: Res<Time>) {
+    if !boundary.dropzones.iter().any(Dropzone::is_animated) {
+        return;
+    }
+
+    let dt = time.delta_seconds();
+    let WorldBoundaryResource {
+        bounds_min,
+        bounds_max,
+        dropzones,
+        ..
+    } = boundary.as_mut();
+
+    for dropzone in dropzones.iter_mut() {
+        let size = (dropzone.size() - Vec2::splat(dropzone.shrink_rate * dt))
+            .max(dropzone.min_size)
+            .min(*bounds_max - *bounds_min);
+        let mut center = dropzone.center() + dropzone.velocity * dt;
+
+        // bounce off the world bounds
+        let half_size = size / 2.0;
+        if center.x - half_size.x < bounds_min.x || center.x + half_size.x > bounds_max.x {
+            dropzone.velocity.x = -dropzone.velocity.x;
+        }
+        if center.y - half_size.y < bounds_min.y || center.y + half_size.y > bounds_max.y {
+            dropzone.velocity.y = -dropzone.velocity.y;
+        }
+        center = center.clamp(*bounds_min + half_size, *bounds_max - half_size);
+
+        dropzone.min = center - half_size;
+        dropzone.max = center + half_size;
+    }
+}
+
+fn update_dropzone_shapes_system(
+    boundary: Res<WorldBoundaryResource>,
+    mut shapes: Query<(&mut Transform, &DropzoneShape)>,
+) {
+    for (mut transform, shape) in shapes.iter_mut() {
+        if let Some(dropzone) = boundary.dropzones.get(shape.index) {
+            transform.translation = dropzone.center().extend(CAMERA_Z_BACKGROUND);
+            transform.scale = (dropzone.size() / shape.base_size).extend(1.0);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+/// Static level geometry that blocks icons, projectiles and the player
+#[derive(Debug, Clone)]
+pub enum Obstacle {
+    Rectangle {
+        center: Vec2,
+        size: Vec2,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    /// Closed polygon, the points should not self-intersect
+    Polygon {
+        points: Vec<Vec2>,
+    },
+}
+
+/// Closest point to `point` on the line segment from `a` to `b`
+fn closest_point_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return a;
+    }
+    let t = ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+impl Obstacle {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Obstacle::Rectangle { center, size } => {
+                let offset = (point - *center).abs();
+                offset.x <= size.x / 2.0 && offset.y <= size.y / 2.0
+            }
+            Obstacle::Circle { center, radius } => {
+                point.distance_squared(*center) <= radius * radius
+            }
+            Obstacle::Polygon { points } => {
+                // even-odd rule, count crossings of a ray to the right
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > point.y) != (b.y > point.y)
+                        && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    /// Closest point on the outline of the obstacle
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        match self {
+            Obstacle::Rectangle { center, size } => {
+                let half_size = *size / 2.0;
+                let offset = point - *center;
+                let clamped = offset.clamp(-half_size, half_size);
+                if clamped != offset {
+                    return *center + clamped;
+                }
+                // inside, move to the nearest edge
+                let distance = half_size - offset.abs();
+                if distance.x < distance.y {
+                    *center + Vec2::new(half_size.x.copysign(offset.x), offset.y)
+                } else {
+                    *center + Vec2::new(offset.x, half_size.y.copysign(offset.y))
+                }
+            }
+            Obstacle::Circle { center, radius } => {
+                let direction = (point - *center).try_normalize().unwrap_or(Vec2::Y);
+                *center + direction * *radius
+            }
+            Obstacle::Polygon { points } => points
+                .iter()
+                .enumerate()
+                .map(|(i, a)| closest_point_on_segment(point, *a, points[(i + 1) % points.len()]))
+                .min_by(|a, b| {
+                    a.distance_squared(point)
+                        .total_cmp(&b.distance_squared(point))
+                })
+                .unwrap_or(point),
+        }
+    }
+
+    /// Direction pointing away from the obstacle and the distance to its outline,
+    /// the distance is negative inside of the obstacle
+    pub fn signed_distance(&self, point: Vec2) -> (Vec2, f32) {
+        let closest = self.closest_point(point);
+        let offset = point - closest;
+        let distance = offset.length();
+        let normal = offset.try_normalize().unwrap_or(Vec2::Y);
+        if self.contains(point) {
+            (-normal, -distance)
+        } else {
+            (normal, distance)
+        }
+    }
+
+    /// Axis aligned bounding box as min and max corner
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match self {
+            Obstacle::Rectangle { center, size } => (*center - *size / 2.0, *center + *size / 2.0),
+            Obstacle::Circle { center, radius } => (
+                *center - Vec2::splat(*radius),
+                *center + Vec2::splat(*radius),
+            ),
+            Obstacle::Polygon { points } => points.iter().fold(
+                (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+                |(min, max), point| (min.min(*point), max.max(*point)),
+            ),
+        }
+    }
+
+    /// Moves a circle of `radius` out of the obstacle, returns the push direction if it overlapped
+    pub fn resolve_collision(&self, position: &mut Vec2, radius: f32) -> Option<Vec2> {
+        let (normal, distance) = self.signed_distance(*position);
+        if distance >= radius {
+            return None;
+        }
+        *position += normal * (radius - distance);
+        Some(normal)
+    }
+
+    pub fn add_to_builder(&self, builder: GeometryBuilder) -> GeometryBuilder {
+        match self {
+            Obstacle::Rectangle { center, size } => builder.add(&shapes::Rectangle {
+                extents: *size,
+                origin: RectangleOrigin::CustomCenter(*center),
+            }),
+            Obstacle::Circle { center, radius } => builder.add(&shapes::Circle {
+                radius: *radius,
+                center: *center,
+            }),
+            Obstacle::Polygon { points } => builder.add(&shapes::Polygon {
+                points: points.clone(),
+                closed: true,
+            }),
+        }
+    }
+}